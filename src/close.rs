@@ -1,31 +1,100 @@
+use crate::close::budget::{Budget, read_budget_config};
+use crate::close::charts::ChartData;
 use crate::close::close_xml::do_closing_xml;
-use crate::close::close_xml::read_budget_config;
+use crate::close::close_xml::{Closing, DELTA_PREVIOUS_YEAR, RowType};
+use crate::close::cost_centres::COST_CENTRE_STATEMENT;
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::close::integrity::{INTEGRITY_CHECKS, summarize};
 use crate::derive_month_from_accounts;
 use crate::export::path_with_prefix;
+use crate::money::{as_float, as_money};
 
 use polars::prelude::*;
 use polars_excel_writer::PolarsExcelWriter;
-use rust_xlsxwriter::{Color, Format, FormatAlign, Workbook};
+use rust_xlsxwriter::{
+    Chart, ChartType, Color, ConditionalFormatCell, ConditionalFormatCellRule, Format, FormatAlign,
+    FormatBorder, FormatUnderline, Url, Workbook, Worksheet, XlsxError,
+};
+use std::collections::HashMap;
 use std::error::Error;
 use std::path::Path;
+use std::str::FromStr;
 use std::vec;
 
+mod account_pattern;
+mod balance_sheet;
+mod budget;
+mod budget_check;
+mod budget_init;
+mod budget_propose;
+mod charts;
 mod close_xml;
+mod cost_centres;
+mod cross_check;
+mod drill_down;
+mod fiscal_year;
+mod integrity;
 
-/// Read the file with the accounts information and create the closing file
+/// The name of the worksheet listing the journal lines per post
+const DRILL_DOWN: &str = "buchungen";
+
+/// The name of the worksheet holding the data the charts are built from
+const CHART_DATA: &str = "diagrammdaten";
+
+pub use crate::close::budget_check::check_budget;
+pub use crate::close::budget_init::init_budget;
+pub use crate::close::budget_propose::propose_budget;
+
+/// The reporting period of the closing, by default the fiscal year up to and including
+/// the month of the accounts file
+#[derive(Debug)]
+pub struct PeriodOptions {
+    /// The first month to report (`yyyymm`), defaults to the start of the fiscal year
+    pub from: Option<String>,
+    /// The last month to report (`yyyymm`), defaults to the month of the accounts file
+    pub to: Option<String>,
+    /// The quarter of the fiscal year to report (`yyyyQn`), instead of `from` and `to`
+    pub quarter: Option<String>,
+}
+
+/// Read the file with the accounts information and create the closing file for the `period`.
+/// If the accounts file of the previous year is provided, the closing file
+/// additionally compares the figures with the ones of the previous year.
+/// If a `scenario` is provided, the budget amounts are taken from the scenario of that name.
 pub fn close(
     budget_config_file: &Path,
     accounts_file: &Path,
+    previous_accounts_file: Option<&Path>,
+    period: &PeriodOptions,
+    scenario: Option<&str>,
     ts: &str,
 ) -> Result<(), Box<dyn Error>> {
     let account_file_name = accounts_file.as_os_str().to_str();
     if let Some(extension) = accounts_file.extension().and_then(|e| e.to_str()) {
         let month = derive_month_from_accounts(account_file_name, extension)?;
         let budget = read_budget_config(budget_config_file)?;
+        let period = reporting_period(&budget, &month, period)?;
+        let budget = match scenario {
+            Some(scenario) => budget.with_scenario(scenario, &period.budget_year())?,
+            None => budget,
+        };
         match extension {
             "xls" => {
-                let df = do_closing_xml(accounts_file, budget, &month)?;
-                write_closing_to_file(&df, "closing", &month, ts)?;
+                let closing =
+                    do_closing_xml(accounts_file, previous_accounts_file, &budget, &period)?;
+                println!("{}", summarize(&closing.checks)?);
+                if let Some(differences) = closing
+                    .previous_differences
+                    .as_ref()
+                    .filter(|d| d.height() > 0)
+                {
+                    println!(
+                        "Warning: balances of {} accounts or totals of the previous year differ \
+                        from the ones computed by Banana, see worksheet {INTEGRITY_CHECKS}",
+                        differences.height()
+                    );
+                }
+                write_closing_to_file(&closing, "closing", &period.file_label(), ts)?;
                 Ok(())
             }
             _ => Err(Box::from(format!(
@@ -37,25 +106,55 @@ pub fn close(
     }
 }
 
+/// Determines the reporting period from the `options`: the quarter of the fiscal year if specified,
+/// otherwise the months `from` (default: the start of the fiscal year) to `to` (default: `month`).
+/// The period must not end after `month`, the month of the accounts file.
+fn reporting_period(
+    budget: &Budget,
+    month: &str,
+    options: &PeriodOptions,
+) -> Result<FiscalPeriod, Box<dyn Error>> {
+    let period = if let Some(quarter) = &options.quarter {
+        FiscalPeriod::quarter(quarter, budget.fiscal_year_start)?
+    } else {
+        let period = budget.fiscal_period(options.to.as_deref().unwrap_or(month))?;
+        match &options.from {
+            Some(from) => period.starting_from(from)?,
+            None => period,
+        }
+    };
+    if period.closing_month().as_str() > month {
+        return Err(format!(
+            "Invalid period ending {}, expected it to end not later than the month {month} of the accounts file",
+            period.closing_month()
+        )
+        .into());
+    }
+    Ok(period)
+}
+
 /// Creates a dataframe with dummy postings to extend the actual postings from the ledger
-/// The purpose is to have at least one real or dummy posting per post.
-fn as_dataframe(accounts: Vec<String>, month: &str) -> PolarsResult<DataFrame> {
+/// The purpose is to have at least one real or dummy posting per post, dated on the `date` specified.
+fn as_dataframe(accounts: Vec<String>, date: &str) -> PolarsResult<DataFrame> {
     let length = accounts.len();
-    let year: String = month.chars().take(4).collect();
-    let dates = vec![format!("{year}-01-01"); length];
+    let dates = vec![date.to_string(); length];
     let descriptions = vec!["Dummy post"; length];
     let amounts = vec![0.0; length];
-    df!(
+    let no_cost_centres = vec![""; length];
+    as_money(&df!(
         "Date" => dates,
         "Description" => descriptions,
         "Debit" => accounts.clone(),
         "Credit" => accounts,
         "Amount" => amounts,
-    )
+        "Cc1" => no_cost_centres.clone(),
+        "Cc2" => no_cost_centres.clone(),
+        "Cc3" => no_cost_centres,
+    )?)
 }
 
 fn write_closing_to_file(
-    df: &DataFrame,
+    closing: &Closing,
     prefix: &str,
     month: &str,
     ts: &str,
@@ -69,47 +168,406 @@ fn write_closing_to_file(
     let worksheet = workbook.add_worksheet().set_name(prefix)?;
     excel_writer.set_freeze_panes(1, 1);
 
-    excel_writer.write_dataframe_to_worksheet(df, worksheet, 0, 0)?;
-
-    let last_row = u32::try_from(df.shape().0)? + 2;
-    //    let last_col = u16::try_from(df.shape().1)?;
+    let df = &closing.summary;
+    let row_types = df
+        .column("Row Type")?
+        .str()?
+        .into_iter()
+        .map(|rt| RowType::from_str(rt.unwrap_or_default()))
+        .collect::<Result<Vec<RowType>, _>>()?;
+    let report = df.drop("Row Type")?;
+    excel_writer.write_dataframe_to_worksheet(&as_float(&report)?, worksheet, 0, 0)?;
 
     // Budget groups and posts
-    let col = 0;
-    let format = Format::new().set_background_color(Color::RGB(0x00B2_B2B2));
-    worksheet.set_range_format(0, col, last_row, col, &format)?;
-    let format = format.clone().set_bold();
-    worksheet.write_with_format(0, col, "", &format)?;
-    worksheet.write_with_format(last_row, col, "Rein-Gew./-Verlust", &format)?;
+    format_column(worksheet, 0, &row_types, 0x00B2_B2B2, "General")?;
+    let format = Format::new()
+        .set_background_color(Color::RGB(0x00B2_B2B2))
+        .set_bold();
+    worksheet.write_with_format(0, 0, "", &format)?;
+    link_posts_to_drill_down(worksheet, &report, &row_types, &closing.drill_down)?;
 
     // Budget
-    let col = 1;
-    let format = Format::new()
-        .set_background_color(Color::RGB(0x00b4_c7dc))
-        .set_num_format("#,##0.00");
-    worksheet.set_range_format(0, col, last_row, col, &format)?;
-    let format = format.clone().set_bold().set_align(FormatAlign::Right);
-    worksheet.set_range_format(0, col, 0, col, &format)?;
+    format_column(worksheet, 1, &row_types, 0x00b4_c7dc, "#,##0.00")?;
 
     // Months
-    let first_col = 2;
-    let last_col = 2;
-    let format = Format::new()
-        .set_background_color(Color::RGB(0x00ff_ffd7))
-        .set_num_format("#'##0.00");
-    worksheet.set_range_format(0, first_col, last_row, last_col, &format)?;
-    let format = format.clone().set_bold().set_align(FormatAlign::Right);
-    worksheet.set_range_format(0, first_col, 0, last_col, &format)?;
+    format_column(worksheet, 2, &row_types, 0x00ff_ffd7, "#'##0.00")?;
 
     // Verbleibend
-    let col = 3;
-    let format = Format::new()
-        .set_background_color(Color::RGB(0x00af_d095))
-        .set_num_format("#'##0.00");
-    worksheet.set_range_format(0, col, last_row, col, &format)?;
-    let format = format.clone().set_bold().set_align(FormatAlign::Right);
-    worksheet.set_range_format(0, col, 0, col, &format)?;
+    format_column(worksheet, 3, &row_types, 0x00af_d095, "#'##0.00")?;
+
+    // Budget YTD
+    format_column(worksheet, 4, &row_types, 0x00b4_c7dc, "#'##0.00")?;
+
+    // Abweichung YTD
+    format_column(worksheet, 5, &row_types, 0x00af_d095, "#'##0.00")?;
+
+    // Prognose
+    format_column(worksheet, 6, &row_types, 0x00ff_ffd7, "#'##0.00")?;
+
+    // Abweichung Prognose, highlighting a shortfall compared to the budget
+    format_column(worksheet, 7, &row_types, 0x00af_d095, "#'##0.00")?;
+    highlight_shortfall(worksheet, 7, u32::try_from(row_types.len())?)?;
+
+    // Previous year: the same period and the total, followed by the deviation
+    if let Some(col) = report.get_column_index(DELTA_PREVIOUS_YEAR) {
+        let col = u16::try_from(col)?;
+        format_column(worksheet, col - 2, &row_types, 0x00e6_e6e6, "#'##0.00")?;
+        format_column(worksheet, col - 1, &row_types, 0x00e6_e6e6, "#'##0.00")?;
+        format_column(worksheet, col, &row_types, 0x00e6_e6e6, "0.0")?;
+    }
+
+    write_charts(&mut workbook, &closing.charts)?;
+    write_drill_down(&mut workbook, &closing.drill_down)?;
+    write_cost_centres(&mut workbook, &closing.cost_centres)?;
+    write_balance_sheet(&mut workbook, &closing.balance_sheet)?;
+    write_excluded_accounts(&mut workbook, &closing.excluded)?;
+    write_integrity_checks(
+        &mut workbook,
+        &closing.checks,
+        closing.previous_differences.as_ref(),
+    )?;
 
     workbook.save(path)?;
     Ok(())
 }
+
+/// Replaces the names of the posts in the first column with links
+/// to their section on the drill-down worksheet.
+fn link_posts_to_drill_down(
+    worksheet: &mut Worksheet,
+    report: &DataFrame,
+    row_types: &[RowType],
+    drill_down: &DataFrame,
+) -> Result<(), Box<dyn Error>> {
+    let mut sections = HashMap::new();
+    for (row, (row_type, description)) in (2_u32..).zip(
+        drill_down
+            .column("Row Type")?
+            .str()?
+            .into_iter()
+            .zip(drill_down.column("Description")?.str()?),
+    ) {
+        if let (Some(row_type), Some(description)) = (row_type, description)
+            && row_type == RowType::Header.to_string()
+        {
+            sections.insert(description.to_string(), row);
+        }
+    }
+    let link_format = Format::new()
+        .set_background_color(Color::RGB(0x00B2_B2B2))
+        .set_font_color(Color::Blue)
+        .set_underline(FormatUnderline::Single);
+    for ((row, row_type), post) in (1_u32..).zip(row_types).zip(report.column("Group")?.str()?) {
+        if let (RowType::Post, Some(post)) = (row_type, post)
+            && let Some(section_row) = sections.get(post)
+        {
+            let url = Url::new(format!("internal:'{DRILL_DOWN}'!A{section_row}")).set_text(post);
+            worksheet.write_url_with_format(row, 0, url, &link_format)?;
+        }
+    }
+    Ok(())
+}
+
+/// Draws the budget and the figures of the period per post group (clustered bars),
+/// the monthly net result (lines) and the revenue composition (pie) on a separate worksheet.
+/// The charts refer to the data written to a helper worksheet; charts without data are omitted.
+fn write_charts(workbook: &mut Workbook, charts: &ChartData) -> Result<(), Box<dyn Error>> {
+    let mut excel_writer = PolarsExcelWriter::new();
+    excel_writer.set_autofit(true);
+    excel_writer.set_dtype_float_format("#'##0.00");
+    let mut data_sheet = Worksheet::new();
+    data_sheet.set_name(CHART_DATA)?;
+    let mut chart_sheet = Worksheet::new();
+    chart_sheet.set_name("diagramme")?;
+
+    let (groups_col, monthly_col, revenue_col) = (0_u16, 4_u16, 8_u16);
+    let mut chart_row = 0_u32;
+    if charts.groups.height() > 0 {
+        excel_writer.write_dataframe_to_worksheet(
+            &as_float(&charts.groups)?,
+            &mut data_sheet,
+            0,
+            groups_col,
+        )?;
+        let last_row = u32::try_from(charts.groups.height())?;
+        let mut chart = Chart::new(ChartType::Bar);
+        for col in [groups_col + 1, groups_col + 2] {
+            chart
+                .add_series()
+                .set_name((CHART_DATA, 0, col))
+                .set_categories((CHART_DATA, 1, groups_col, last_row, groups_col))
+                .set_values((CHART_DATA, 1, col, last_row, col));
+        }
+        chart.title().set_name("Budget und Ist pro Postengruppe");
+        chart.set_width(720).set_height(360);
+        chart_sheet.insert_chart(chart_row, 0, &chart)?;
+        chart_row += 20;
+    }
+    if charts.monthly.height() > 0 {
+        excel_writer.write_dataframe_to_worksheet(
+            &as_float(&charts.monthly)?,
+            &mut data_sheet,
+            0,
+            monthly_col,
+        )?;
+        let last_row = u32::try_from(charts.monthly.height())?;
+        let mut chart = Chart::new(ChartType::Line);
+        for col in [monthly_col + 1, monthly_col + 2] {
+            chart
+                .add_series()
+                .set_name((CHART_DATA, 0, col))
+                .set_categories((CHART_DATA, 1, monthly_col, last_row, monthly_col))
+                .set_values((CHART_DATA, 1, col, last_row, col));
+        }
+        chart.title().set_name("Ergebnis pro Monat");
+        chart.set_width(720).set_height(360);
+        chart_sheet.insert_chart(chart_row, 0, &chart)?;
+        chart_row += 20;
+    }
+    if charts.revenue.height() > 0 {
+        excel_writer.write_dataframe_to_worksheet(
+            &as_float(&charts.revenue)?,
+            &mut data_sheet,
+            0,
+            revenue_col,
+        )?;
+        let last_row = u32::try_from(charts.revenue.height())?;
+        let mut chart = Chart::new(ChartType::Pie);
+        chart
+            .add_series()
+            .set_name((CHART_DATA, 0, revenue_col + 1))
+            .set_categories((CHART_DATA, 1, revenue_col, last_row, revenue_col))
+            .set_values((CHART_DATA, 1, revenue_col + 1, last_row, revenue_col + 1));
+        chart.title().set_name("Zusammensetzung Ertrag");
+        chart.set_width(720).set_height(360);
+        chart_sheet.insert_chart(chart_row, 0, &chart)?;
+    }
+    workbook.push_worksheet(chart_sheet);
+    workbook.push_worksheet(data_sheet);
+    Ok(())
+}
+
+/// Lists the journal lines per post on a separate worksheet,
+/// with the header and subtotal rows of the posts highlighted.
+fn write_drill_down(workbook: &mut Workbook, drill_down: &DataFrame) -> Result<(), Box<dyn Error>> {
+    let mut excel_writer = PolarsExcelWriter::new();
+    excel_writer.set_autofit(true);
+    excel_writer.set_freeze_panes(1, 0);
+    let worksheet = workbook.add_worksheet().set_name(DRILL_DOWN)?;
+    let row_types = drill_down
+        .column("Row Type")?
+        .str()?
+        .into_iter()
+        .map(|rt| RowType::from_str(rt.unwrap_or_default()))
+        .collect::<Result<Vec<RowType>, _>>()?;
+    let lines = drill_down.drop("Row Type")?;
+    write_dataframe_or_header(&mut excel_writer, &lines, worksheet)?;
+    for col in 0..u16::try_from(lines.width())? {
+        let num_format = if col == 4 { "#'##0.00" } else { "General" };
+        format_column(worksheet, col, &row_types, 0x00FF_FFFF, num_format)?;
+    }
+    Ok(())
+}
+
+/// Lists the profit and loss statement per cost centre on a separate worksheet.
+fn write_cost_centres(
+    workbook: &mut Workbook,
+    cost_centres: &DataFrame,
+) -> Result<(), Box<dyn Error>> {
+    let mut excel_writer = PolarsExcelWriter::new();
+    excel_writer.set_autofit(true);
+    excel_writer.set_freeze_panes(1, 1);
+    let worksheet = workbook.add_worksheet().set_name(COST_CENTRE_STATEMENT)?;
+    let row_types = cost_centres
+        .column("Row Type")?
+        .str()?
+        .into_iter()
+        .map(|rt| RowType::from_str(rt.unwrap_or_default()))
+        .collect::<Result<Vec<RowType>, _>>()?;
+    let statement = cost_centres.drop("Row Type")?;
+    write_dataframe_or_header(&mut excel_writer, &statement, worksheet)?;
+    format_column(worksheet, 0, &row_types, 0x00B2_B2B2, "General")?;
+    format_column(worksheet, 1, &row_types, 0x00b4_c7dc, "#'##0.00")?;
+    format_column(worksheet, 2, &row_types, 0x00ff_ffd7, "#'##0.00")?;
+    format_column(worksheet, 3, &row_types, 0x00af_d095, "#'##0.00")?;
+    Ok(())
+}
+
+/// Sets the font of the negative values of column `col` in red (in favour of a deficit)
+/// and the one of the positive values in green.
+fn highlight_shortfall(worksheet: &mut Worksheet, col: u16, rows: u32) -> Result<(), XlsxError> {
+    let shortfall = ConditionalFormatCell::new()
+        .set_rule(ConditionalFormatCellRule::LessThan(0))
+        .set_format(Format::new().set_font_color(Color::Red));
+    let surplus = ConditionalFormatCell::new()
+        .set_rule(ConditionalFormatCellRule::GreaterThan(0))
+        .set_format(Format::new().set_font_color(Color::RGB(0x0000_8000)));
+    worksheet.add_conditional_format(1, col, rows, col, &shortfall)?;
+    worksheet.add_conditional_format(1, col, rows, col, &surplus)?;
+    Ok(())
+}
+
+/// Lists the balance sheet accounts on a separate worksheet,
+/// highlighting the remarks on transitory accounts not being balanced.
+fn write_balance_sheet(
+    workbook: &mut Workbook,
+    balance_sheet: &DataFrame,
+) -> Result<(), Box<dyn Error>> {
+    let mut excel_writer = PolarsExcelWriter::new();
+    excel_writer.set_autofit(true);
+    excel_writer.set_dtype_float_format("#'##0.00");
+    excel_writer.set_freeze_panes(1, 1);
+    let worksheet = workbook.add_worksheet().set_name("bilanz")?;
+    write_dataframe_or_header(&mut excel_writer, balance_sheet, worksheet)?;
+
+    if let Some(check_col) = balance_sheet.get_column_index("Check") {
+        let warning_format = Format::new().set_bold().set_font_color(Color::Red);
+        for (row, check) in (1_u32..).zip(balance_sheet.column("Check")?.str()?) {
+            if let Some(check) = check.filter(|c| !c.is_empty()) {
+                worksheet.write_with_format(
+                    row,
+                    u16::try_from(check_col)?,
+                    check,
+                    &warning_format,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lists the accounts excluded from the closing with their balances on a separate worksheet,
+/// so no posting disappears unnoticed.
+fn write_excluded_accounts(
+    workbook: &mut Workbook,
+    excluded: &DataFrame,
+) -> Result<(), Box<dyn Error>> {
+    let mut excel_writer = PolarsExcelWriter::new();
+    excel_writer.set_autofit(true);
+    excel_writer.set_dtype_float_format("#'##0.00");
+    let worksheet = workbook
+        .add_worksheet()
+        .set_name("ausgeschlossene konten")?;
+    write_dataframe_or_header(&mut excel_writer, excluded, worksheet)?;
+    Ok(())
+}
+
+/// Lists the journal lines failing the integrity checks on a separate worksheet,
+/// followed by the differing balances of the previous year (if any).
+fn write_integrity_checks(
+    workbook: &mut Workbook,
+    checks: &DataFrame,
+    previous_differences: Option<&DataFrame>,
+) -> Result<(), Box<dyn Error>> {
+    let mut excel_writer = PolarsExcelWriter::new();
+    excel_writer.set_autofit(true);
+    excel_writer.set_dtype_float_format("#'##0.00");
+    excel_writer.set_freeze_panes(1, 0);
+    let worksheet = workbook.add_worksheet().set_name(INTEGRITY_CHECKS)?;
+    write_dataframe_or_header(&mut excel_writer, checks, worksheet)?;
+    if let Some(differences) = previous_differences.filter(|d| d.height() > 0) {
+        let row = u32::try_from(checks.height())? + 2;
+        worksheet.write_string_with_format(
+            row,
+            0,
+            "Abweichungen Saldi Vorjahr",
+            &Format::new().set_bold(),
+        )?;
+        excel_writer.write_dataframe_to_worksheet(
+            &as_float(differences)?,
+            worksheet,
+            row + 1,
+            0,
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the dataframe (with its amounts as floating point numbers) to the worksheet,
+/// or just its column names if it is empty,
+/// as an Excel table requires at least one row.
+fn write_dataframe_or_header(
+    excel_writer: &mut PolarsExcelWriter,
+    df: &DataFrame,
+    worksheet: &mut Worksheet,
+) -> Result<(), Box<dyn Error>> {
+    if df.height() == 0 {
+        let header_format = Format::new().set_bold();
+        worksheet.write_row_with_format(0, 0, df.get_column_names_str(), &header_format)?;
+        worksheet.autofit();
+    } else {
+        excel_writer.write_dataframe_to_worksheet(&as_float(df)?, worksheet, 0, 0)?;
+    }
+    Ok(())
+}
+
+/// Applies the background color and number format to column `col`,
+/// with the header in bold and right aligned. Depending on the `row_types`,
+/// group headers are highlighted and subtotals and the result are set in bold.
+fn format_column(
+    worksheet: &mut Worksheet,
+    col: u16,
+    row_types: &[RowType],
+    background: u32,
+    num_format: &str,
+) -> Result<(), XlsxError> {
+    let format = Format::new()
+        .set_background_color(Color::RGB(background))
+        .set_num_format(num_format);
+    let header_format = format.clone().set_bold().set_align(FormatAlign::Right);
+    worksheet.set_range_format(0, col, 0, col, &header_format)?;
+    for (row, row_type) in (1_u32..).zip(row_types) {
+        let row_format = match row_type {
+            RowType::Header => Format::new()
+                .set_background_color(Color::RGB(0x0080_8080))
+                .set_font_color(Color::White)
+                .set_bold(),
+            RowType::Post => format.clone(),
+            RowType::Subtotal => format.clone().set_bold().set_border_top(FormatBorder::Thin),
+            RowType::Result => format
+                .clone()
+                .set_bold()
+                .set_border_top(FormatBorder::Thin)
+                .set_border_bottom(FormatBorder::Double),
+        };
+        worksheet.set_range_format(row, col, row, col, &row_format)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    #[rstest]
+    #[case(None, Some("202412"), None, "202412")]
+    #[case(Some("202410"), Some("202411"), None, "202410-202411")]
+    #[case(None, None, Some("2024Q4"), "202410-202412")]
+    #[case(None, Some("202501"), None, "")]
+    #[case(None, None, Some("2025Q1"), "")]
+    fn test_reporting_period(
+        #[case] from: Option<&str>,
+        #[case] to: Option<&str>,
+        #[case] quarter: Option<&str>,
+        #[case] file_label: &str,
+    ) {
+        let budget =
+            read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid budget");
+        let options = PeriodOptions {
+            from: from.map(String::from),
+            to: to.map(String::from),
+            quarter: quarter.map(String::from),
+        };
+        let period = reporting_period(&budget, "202412", &options);
+        if file_label.is_empty() {
+            assert!(
+                period.is_err(),
+                "period after the accounts file is rejected"
+            );
+        } else {
+            assert_eq!(period.expect("valid period").file_label(), file_label);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// An entry of the `account_codes` of a budget post:
+/// a single account (`30100`), a range of accounts (`30100-30999`)
+/// or all accounts starting with a prefix (`68*`).
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub enum AccountPattern {
+    Exact(String),
+    Range(String, String),
+    Prefix(String),
+}
+
+/// How the sets of accounts covered by two patterns relate to each other
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overlap {
+    /// No account is covered by both patterns
+    Disjoint,
+    /// Both patterns cover the same accounts
+    Identical,
+    /// One pattern covers all accounts of the other one
+    Nested,
+    /// The patterns share some accounts, but each covers accounts the other one does not
+    Partial,
+}
+
+/// The length of the accounts generated from a prefix pattern (e.g. for the dummy postings)
+const DEFAULT_ACCOUNT_LENGTH: usize = 5;
+
+impl TryFrom<String> for AccountPattern {
+    type Error = String;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        let is_numeric = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        if let Some((from, to)) = code.split_once('-') {
+            let (from, to) = (from.trim(), to.trim());
+            if !is_numeric(from) || !is_numeric(to) || from.len() != to.len() {
+                Err(format!(
+                    "Invalid account range '{code}': bounds must be numeric and of same length"
+                ))
+            } else if from > to {
+                Err(format!(
+                    "Invalid account range '{code}': lower bound exceeds upper bound"
+                ))
+            } else {
+                Ok(AccountPattern::Range(from.into(), to.into()))
+            }
+        } else if let Some(prefix) = code.strip_suffix('*') {
+            if is_numeric(prefix) {
+                Ok(AccountPattern::Prefix(prefix.into()))
+            } else {
+                Err(format!(
+                    "Invalid account wildcard '{code}': prefix must be numeric"
+                ))
+            }
+        } else if is_numeric(code.trim()) {
+            Ok(AccountPattern::Exact(code.trim().into()))
+        } else {
+            Err(format!("Invalid account '{code}': must be numeric"))
+        }
+    }
+}
+
+impl fmt::Display for AccountPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountPattern::Exact(account) => write!(f, "{account}"),
+            AccountPattern::Range(from, to) => write!(f, "{from}-{to}"),
+            AccountPattern::Prefix(prefix) => write!(f, "{prefix}*"),
+        }
+    }
+}
+
+impl AccountPattern {
+    /// Returns true if the account is covered by the pattern
+    pub fn matches(&self, account: &str) -> bool {
+        match self {
+            AccountPattern::Exact(code) => code == account,
+            AccountPattern::Range(from, to) => {
+                account.len() == from.len()
+                    && account.chars().all(|c| c.is_ascii_digit())
+                    && from.as_str() <= account
+                    && account <= to.as_str()
+            }
+            AccountPattern::Prefix(prefix) => account.starts_with(prefix.as_str()),
+        }
+    }
+
+    /// Compares the specificity of two patterns matching the same account:
+    /// The pattern covering fewer accounts of the length of `account` is the more specific one (`Less`).
+    /// On a tie, an exact account precedes a range, which precedes a prefix.
+    pub fn cmp_specificity(&self, other: &Self, account: &str) -> Ordering {
+        (self.coverage(account.len()), self.rank())
+            .cmp(&(other.coverage(account.len()), other.rank()))
+    }
+
+    /// The length of the accounts representing the pattern (e.g. for dummy postings),
+    /// with accounts of a prefix pattern having at least five digits
+    pub fn account_length(&self) -> usize {
+        self.fixed_length()
+            .unwrap_or(DEFAULT_ACCOUNT_LENGTH.max(self.prefix_length()))
+    }
+
+    /// Determines how the accounts covered by this and the other pattern overlap.
+    pub fn overlap(&self, other: &Self) -> Overlap {
+        let length = match (self.fixed_length(), other.fixed_length()) {
+            (Some(a), Some(b)) if a != b => return Overlap::Disjoint,
+            (Some(length), _) | (_, Some(length)) => length,
+            (None, None) => self.prefix_length().max(other.prefix_length()),
+        };
+        let (Some((a_from, a_to)), Some((b_from, b_to))) =
+            (self.interval(length), other.interval(length))
+        else {
+            return Overlap::Disjoint;
+        };
+        if a_to < b_from || b_to < a_from {
+            Overlap::Disjoint
+        } else if a_from == b_from && a_to == b_to {
+            Overlap::Identical
+        } else if (a_from <= b_from && b_to <= a_to) || (b_from <= a_from && a_to <= b_to) {
+            Overlap::Nested
+        } else {
+            Overlap::Partial
+        }
+    }
+
+    /// The number of accounts of given length covered by the pattern
+    fn coverage(&self, length: usize) -> u128 {
+        self.interval(length).map_or(0, |(from, to)| to - from + 1)
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            AccountPattern::Exact(_) => 0,
+            AccountPattern::Range(_, _) => 1,
+            AccountPattern::Prefix(_) => 2,
+        }
+    }
+
+    /// The length of the accounts covered, unless the pattern is a prefix
+    fn fixed_length(&self) -> Option<usize> {
+        match self {
+            AccountPattern::Exact(account) => Some(account.len()),
+            AccountPattern::Range(from, _) => Some(from.len()),
+            AccountPattern::Prefix(_) => None,
+        }
+    }
+
+    fn prefix_length(&self) -> usize {
+        match self {
+            AccountPattern::Prefix(prefix) => prefix.len(),
+            _ => 0,
+        }
+    }
+
+    /// The numeric interval of the accounts of given length covered by the pattern
+    pub fn interval(&self, length: usize) -> Option<(u128, u128)> {
+        let parse = |s: &str| s.parse::<u128>().ok();
+        match self {
+            AccountPattern::Exact(account) if account.len() == length => {
+                parse(account).map(|a| (a, a))
+            }
+            AccountPattern::Range(from, to) if from.len() == length => {
+                Some((parse(from)?, parse(to)?))
+            }
+            AccountPattern::Prefix(prefix) if prefix.len() <= length => {
+                let factor = 10_u128.checked_pow(u32::try_from(length - prefix.len()).ok()?)?;
+                let from = parse(prefix)? * factor;
+                Some((from, from + factor - 1))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn pattern(code: &str) -> AccountPattern {
+        AccountPattern::try_from(code.to_string()).expect("valid account pattern")
+    }
+
+    #[rstest]
+    #[case("30100", AccountPattern::Exact("30100".into()))]
+    #[case("30100-30999", AccountPattern::Range("30100".into(), "30999".into()))]
+    #[case("68*", AccountPattern::Prefix("68".into()))]
+    fn can_parse_pattern(#[case] code: &str, #[case] expected: AccountPattern) {
+        let actual = pattern(code);
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), code);
+    }
+
+    #[rstest]
+    #[case("30999-30100")]
+    #[case("3010-30999")]
+    #[case("3a*")]
+    #[case("3010a")]
+    #[case("")]
+    fn cannot_parse_invalid_pattern(#[case] code: &str) {
+        assert!(AccountPattern::try_from(code.to_string()).is_err());
+    }
+
+    #[rstest]
+    #[case("30100", "30100", true)]
+    #[case("30100", "30101", false)]
+    #[case("30100-30999", "30100", true)]
+    #[case("30100-30999", "30999", true)]
+    #[case("30100-30999", "31000", false)]
+    #[case("30100-30999", "3050", false)]
+    #[case("68*", "68450", true)]
+    #[case("68*", "6845", true)]
+    #[case("68*", "69000", false)]
+    fn test_matches(#[case] code: &str, #[case] account: &str, #[case] expected: bool) {
+        assert_eq!(pattern(code).matches(account), expected);
+    }
+
+    #[rstest]
+    #[case("68450", "68400-68499", Ordering::Less)]
+    #[case("68400-68499", "68*", Ordering::Less)]
+    #[case("684*", "68000-68999", Ordering::Less)]
+    #[case("68000-68999", "68*", Ordering::Less)]
+    #[case("6*", "68000-68999", Ordering::Greater)]
+    fn test_cmp_specificity(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(pattern(a).cmp_specificity(&pattern(b), "68450"), expected);
+    }
+
+    #[rstest]
+    #[case("30100", 5, Some((30100, 30100)))]
+    #[case("0800-0801", 4, Some((800, 801)))]
+    #[case("6*", 5, Some((60000, 69999)))]
+    #[case("680000*", 6, Some((680_000, 680_000)))]
+    fn test_account_length_and_interval(
+        #[case] code: &str,
+        #[case] length: usize,
+        #[case] interval: Option<(u128, u128)>,
+    ) {
+        let pattern = pattern(code);
+        assert_eq!(pattern.account_length(), length);
+        assert_eq!(pattern.interval(length), interval);
+    }
+
+    #[rstest]
+    #[case("30100", "30200", Overlap::Disjoint)]
+    #[case("30100", "30100", Overlap::Identical)]
+    #[case("30100-30199", "30200-30299", Overlap::Disjoint)]
+    #[case("30100-30599", "30500-30999", Overlap::Partial)]
+    #[case("30100-30999", "30500", Overlap::Nested)]
+    #[case("30000-30999", "30*", Overlap::Identical)]
+    #[case("30500-31499", "30*", Overlap::Partial)]
+    #[case("3*", "30*", Overlap::Nested)]
+    #[case("8900", "89*", Overlap::Nested)]
+    #[case("8900", "89000-89999", Overlap::Disjoint)]
+    fn test_overlap(#[case] a: &str, #[case] b: &str, #[case] expected: Overlap) {
+        assert_eq!(pattern(a).overlap(&pattern(b)), expected);
+        assert_eq!(pattern(b).overlap(&pattern(a)), expected);
+    }
+}
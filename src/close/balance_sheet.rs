@@ -0,0 +1,162 @@
+use crate::close::budget::Budget;
+use crate::close::close_xml::account_descriptions;
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::money::zero;
+use polars::prelude::*;
+use std::error::Error;
+
+/// Regular expression matching the balance sheet accounts (assets and liabilities)
+const BALANCE_SHEET_ACCOUNTS: &str = r"^[12]\d{3,4}$";
+
+/// The remark for transitory accounts not balanced at the cut-off date
+const NOT_BALANCED: &str = "Durchlaufkonto nicht ausgeglichen";
+
+/// Lists the balance sheet accounts with their `Opening` balance (from `accounts`),
+/// the `Debit` and `Credit` movements (from the `journal`) up to and including the closing month
+/// (regardless of the first month of the reporting period)
+/// and the resulting `Balance`. Transitory accounts not balanced are flagged in the column `Check`.
+/// Accounts without opening balance and movements are omitted.
+pub fn balance_sheet(
+    journal: &DataFrame,
+    accounts: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let in_period = col("Date").lt(lit(period.cut_off_date()));
+    let openings = accounts.clone().lazy().select([
+        col("Account"),
+        col("Opening"),
+        zero().alias("Debit"),
+        zero().alias("Credit"),
+    ]);
+    let debits = journal.clone().lazy().filter(in_period.clone()).select([
+        col("Debit").alias("Account"),
+        zero().alias("Opening"),
+        col("Amount").alias("Debit"),
+        zero().alias("Credit"),
+    ]);
+    let credits = journal.clone().lazy().filter(in_period).select([
+        col("Credit").alias("Account"),
+        zero().alias("Opening"),
+        zero().alias("Debit"),
+        col("Amount").alias("Credit"),
+    ]);
+    let descriptions = account_descriptions(accounts);
+
+    let budget = budget.clone();
+    let balances = concat([openings, debits, credits], UnionArgs::default())?
+        .filter(
+            col("Account")
+                .str()
+                .contains(lit(BALANCE_SHEET_ACCOUNTS), false),
+        )
+        .group_by([col("Account")])
+        .agg([
+            col("Opening").sum().round(2, RoundMode::HalfToEven),
+            col("Debit").sum().round(2, RoundMode::HalfToEven),
+            col("Credit").sum().round(2, RoundMode::HalfToEven),
+        ])
+        .with_column(
+            (col("Opening") + col("Debit") - col("Credit"))
+                .round(2, RoundMode::HalfToEven)
+                .alias("Balance"),
+        )
+        .filter(
+            col("Opening")
+                .neq(zero())
+                .or(col("Debit").neq(zero()))
+                .or(col("Credit").neq(zero())),
+        )
+        .join(
+            descriptions,
+            [col("Account")],
+            [col("Account")],
+            JoinType::Left.into(),
+        )
+        .with_column(
+            when(
+                col("Account")
+                    .map(
+                        move |a| {
+                            Ok(a.str()?
+                                .into_iter()
+                                .map(|a| a.map(|a| budget.is_transitory(a)))
+                                .collect::<BooleanChunked>()
+                                .into_column())
+                        },
+                        |_, field| Ok(Field::new(field.name().clone(), DataType::Boolean)),
+                    )
+                    .and(col("Balance").neq(zero())),
+            )
+            .then(lit(NOT_BALANCED))
+            .otherwise(lit(""))
+            .alias("Check"),
+        )
+        .select([
+            col("Account"),
+            col("Description").fill_null(lit("")),
+            col("Opening"),
+            col("Debit"),
+            col("Credit"),
+            col("Balance"),
+            col("Check"),
+        ])
+        .sort(["Account"], SortMultipleOptions::default())
+        .collect()?;
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close::budget::read_budget_config;
+    use crate::money::as_money;
+    use crate::test_fixtures::journal_df_01;
+    use crate::test_utils::assert_dataframe;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_balance_sheet(journal_df_01: DataFrame) {
+        let budget =
+            read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget");
+        let transfers = df!(
+            "Date" => &["2025-02-28", "2025-03-02", "2025-04-30"],
+            "Description" => &["SumUp Februar", "SumUp Februar", "SumUp April"],
+            "Debit" => &["10920", "10200", "10920"],
+            "Credit" => &["30100", "10920", "30100"],
+            "Amount" => &[300.0, 300.0, 80.0],
+            "Cc1" => &["", "", ""],
+            "Cc2" => &["", "", ""],
+            "Cc3" => &["", "", ""],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid journal dataframe");
+        let journal = journal_df_01
+            .vstack(&transfers)
+            .expect("can extend journal");
+        let accounts = df!(
+            "Account" => &["10100", "10200", "10920", "20051"],
+            "Description" => &["Kasse", "Bank", "Durchlaufkonto Sumup", "Durchlaufkonto Miti"],
+            "Opening" => &[500.0, 2000.0, 0.0, 0.0],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid accounts dataframe");
+        let expected = df!(
+            "Account" => &["10100", "10200", "10920"],
+            "Description" => &["Kasse", "Bank", "Durchlaufkonto Sumup"],
+            "Opening" => &[500.0, 2000.0, 0.0],
+            "Debit" => &[1000.0, 300.0, 380.0],
+            "Credit" => &[0.0, 0.0, 300.0],
+            "Balance" => &[1500.0, 2300.0, 80.0],
+            "Check" => &["", "", NOT_BALANCED],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid balance sheet dataframe");
+
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual =
+            balance_sheet(&journal, &accounts, &budget, &period).expect("can create balance sheet");
+        assert_dataframe(&actual, &expected);
+    }
+}
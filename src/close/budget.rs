@@ -0,0 +1,651 @@
+use crate::close::account_pattern::AccountPattern;
+use crate::close::fiscal_year::FiscalPeriod;
+use polars::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+/// The name of the group collecting the posts not assigned to any post group
+const UNGROUPED_POSTS: &str = "Übrige Posten";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Budget {
+    #[serde(rename = "name")]
+    _name: String,
+    pub(super) post_groups: HashMap<String, PostGroup>,
+    pub(super) posts: HashMap<String, Post>,
+    pub(super) years: HashMap<String, Year>,
+    #[serde(default)]
+    pub(super) close: CloseConfig,
+    /// Named monthly weights to be referred to by the `phasing` of the posts
+    #[serde(default)]
+    pub(super) phasing_profiles: HashMap<String, Vec<f64>>,
+    /// The month the fiscal year starts with (`1` to `12`)
+    #[serde(default = "january")]
+    pub(super) fiscal_year_start: u32,
+    /// The cost centres (e.g. projects and events), keyed by their code in the journal
+    #[serde(default)]
+    pub(super) cost_centres: HashMap<String, CostCentre>,
+    /// The scenario the amounts of the years are taken from, if one is selected
+    #[serde(skip)]
+    pub(super) scenario: Option<String>,
+}
+
+fn january() -> u32 {
+    1
+}
+
+/// Selection of the accounts considered in the closing
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub(super) struct CloseConfig {
+    /// Regular expression matching the accounts to be considered
+    pub(super) include: String,
+    /// Accounts (ranges and wildcards included) that are ignored despite matching `include`
+    pub(super) exclude: Vec<AccountPattern>,
+    /// Balance sheet accounts expected to be balanced (zero) at the end of the month
+    pub(super) transitory: Vec<AccountPattern>,
+    /// Journal lines with an amount of at least this size are reported by the integrity checks
+    pub(super) large_amount: f64,
+}
+
+impl Default for CloseConfig {
+    fn default() -> Self {
+        CloseConfig {
+            include: r"^[3456789]\d{3,4}$".into(),
+            exclude: vec![AccountPattern::Exact("8900".into())],
+            transitory: ["10920", "10930", "20051", "20121"]
+                .into_iter()
+                .map(|a| AccountPattern::Exact(a.into()))
+                .collect(),
+            large_amount: 10_000.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(super) struct PostGroup {
+    pub(super) name: String,
+    pub(super) posts: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(super) struct Post {
+    pub(super) name: String,
+    pub(super) account_codes: Vec<AccountPattern>,
+    pub(super) sort: i64,
+    pub(super) factor: i64,
+    #[serde(default)]
+    pub(super) phasing: Phasing,
+}
+
+/// Distribution of the annual budget of a post over the twelve months of the fiscal year
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub(super) enum Phasing {
+    /// Explicit weights of the twelve months, starting with the first month of the fiscal year
+    Weights(Vec<f64>),
+    /// `linear` or the name of one of the `phasing_profiles`
+    Profile(String),
+}
+
+/// The name of the phasing distributing the budget evenly over the months
+pub(super) const LINEAR: &str = "linear";
+
+impl Default for Phasing {
+    fn default() -> Self {
+        Phasing::Profile(LINEAR.into())
+    }
+}
+
+/// Ensures the monthly weights of a phasing are twelve non-negative numbers with a positive sum
+pub(super) fn validate_weights(weights: &[f64]) -> Result<(), String> {
+    if weights.len() != 12 {
+        Err(format!(
+            "expected 12 monthly weights, found {}",
+            weights.len()
+        ))
+    } else if weights.iter().any(|w| *w < 0.0) {
+        Err("monthly weights must not be negative".into())
+    } else if weights.iter().sum::<f64>() <= 0.0 {
+        Err("the sum of the monthly weights must be positive".into())
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(super) struct Year {
+    pub(super) amounts: HashMap<String, f64>,
+    /// Named alternatives to the amounts (e.g. optimistic or revised budgets), keyed by their name
+    #[serde(default)]
+    pub(super) scenarios: HashMap<String, Scenario>,
+}
+
+/// The amounts of a scenario, replacing the ones of the year (posts not listed keep the amount of the year)
+#[derive(Deserialize, Debug, Clone)]
+pub(super) struct Scenario {
+    pub(super) amounts: HashMap<String, f64>,
+}
+
+/// A cost centre (Kostenstelle) with its own budget per post
+#[derive(Deserialize, Debug, Clone)]
+pub(super) struct CostCentre {
+    pub(super) name: String,
+    /// The budget amounts per post, keyed by the year the fiscal year starts in
+    #[serde(default)]
+    pub(super) years: HashMap<String, Year>,
+}
+
+impl Budget {
+    /// Takes the amounts of all years (cost centres included) from the scenario `name` where it is defined,
+    /// failing if the budget `year` does not define it.
+    pub(super) fn with_scenario(
+        mut self,
+        name: &str,
+        year: &str,
+    ) -> Result<Budget, Box<dyn Error>> {
+        if !self
+            .years
+            .get(year)
+            .is_some_and(|y| y.scenarios.contains_key(name))
+        {
+            return Err(format!("Scenario {name} not found in year {year} of the budget").into());
+        }
+        let cost_centre_years = self
+            .cost_centres
+            .values_mut()
+            .flat_map(|c| c.years.values_mut());
+        for year in self.years.values_mut().chain(cost_centre_years) {
+            if let Some(scenario) = year.scenarios.get(name) {
+                year.amounts.extend(scenario.amounts.clone());
+            }
+        }
+        self.scenario = Some(name.into());
+        Ok(self)
+    }
+
+    /// The label of the budget column for the `period`, e.g. `Budget 2025` or `Budget 2025 revised`
+    pub(super) fn label(&self, period: &FiscalPeriod) -> String {
+        match &self.scenario {
+            Some(scenario) => format!("Budget {} {scenario}", period.label()),
+            None => format!("Budget {}", period.label()),
+        }
+    }
+
+    /// The part of the fiscal year up to and including the closing `month` (`yyyymm`)
+    pub(super) fn fiscal_period(&self, month: &str) -> Result<FiscalPeriod, Box<dyn Error>> {
+        FiscalPeriod::new(month, self.fiscal_year_start)
+    }
+
+    /// Get the post by account code
+    pub(super) fn get_post_by_account(&self, account: &str) -> Option<&Post> {
+        self.get_post_key_by_account(account)
+            .and_then(|k| self.posts.get(&k))
+    }
+
+    /// Get the key of the post with the most specific account pattern matching the account code
+    pub(super) fn get_post_key_by_account(&self, account: &str) -> Option<String> {
+        self.posts
+            .iter()
+            .flat_map(|(k, p)| {
+                p.account_codes
+                    .iter()
+                    .filter(|c| c.matches(account))
+                    .map(move |c| (k, c))
+            })
+            .min_by(|(k1, c1), (k2, c2)| c1.cmp_specificity(c2, account).then(k1.cmp(k2)))
+            .map(|(k, _)| k.clone())
+    }
+
+    /// Returns true if the account is excluded from the closing
+    pub(super) fn is_excluded(&self, account: &str) -> bool {
+        self.close.exclude.iter().any(|c| c.matches(account))
+    }
+
+    /// Returns true if the account is a transitory account expected to be balanced
+    pub(super) fn is_transitory(&self, account: &str) -> bool {
+        self.close.transitory.iter().any(|c| c.matches(account))
+    }
+
+    /// get the amount for the given budget account and year
+    pub(super) fn get_budget_amount_by_account(&self, account: &str, year: &str) -> f64 {
+        let post_key_option = self.get_post_key_by_account(account);
+        if let Some(post_key) = post_key_option {
+            let x = self.years.get(year);
+            x.and_then(|y| y.amounts.get(&post_key))
+                .copied()
+                .unwrap_or(-0.0)
+        } else {
+            -0.0
+        }
+    }
+
+    /// get the part of the amount for the given budget account and year
+    /// planned for the `months` of the fiscal year according to the phasing of its post
+    pub(super) fn get_budget_ytd_amount_by_account(
+        &self,
+        account: &str,
+        year: &str,
+        months: Range<usize>,
+    ) -> Result<f64, String> {
+        match self.get_post_key_by_account(account) {
+            Some(post_key) => Ok(self.get_budget_amount_by_account(account, year)
+                * self.get_phasing_share(&post_key, months)?),
+            None => Ok(-0.0),
+        }
+    }
+
+    /// the share of the annual budget of the post planned for the `months` of the fiscal year
+    /// (indexes `0` to `11`, starting with the first month of the fiscal year)
+    fn get_phasing_share(&self, post_key: &str, months: Range<usize>) -> Result<f64, String> {
+        let weights = self.get_phasing_weights(post_key)?;
+        let total: f64 = weights.iter().sum();
+        Ok(weights[months].iter().sum::<f64>() / total)
+    }
+
+    /// the monthly weights of the phasing of the post
+    fn get_phasing_weights(&self, post_key: &str) -> Result<Vec<f64>, String> {
+        let post = self
+            .posts
+            .get(post_key)
+            .ok_or_else(|| format!("Unknown post {post_key}"))?;
+        let weights = match &post.phasing {
+            Phasing::Weights(weights) => weights.clone(),
+            Phasing::Profile(name) => match self.phasing_profiles.get(name) {
+                Some(weights) => weights.clone(),
+                None if name == LINEAR => vec![1.0; 12],
+                None => {
+                    return Err(format!("Unknown phasing profile {name} of post {post_key}"));
+                }
+            },
+        };
+        validate_weights(&weights)
+            .map_err(|e| format!("Invalid phasing of post {post_key}: {e}"))?;
+        Ok(weights)
+    }
+
+    /// Returns the post groups ordered by the lowest sort of their posts,
+    /// each with its name and the keys of its posts.
+    /// Posts not assigned to any group are collected in an additional trailing group.
+    pub(super) fn get_ordered_post_groups(&self) -> Vec<(String, Vec<String>)> {
+        let min_sort = |posts: &Vec<String>| {
+            posts
+                .iter()
+                .filter_map(|k| self.posts.get(k).map(|p| p.sort))
+                .min()
+                .unwrap_or(i64::MAX)
+        };
+        let mut groups: Vec<&PostGroup> = self.post_groups.values().collect();
+        groups.sort_by_key(|g| (min_sort(&g.posts), g.name.clone()));
+        let mut ordered: Vec<(String, Vec<String>)> = groups
+            .into_iter()
+            .map(|g| (g.name.clone(), g.posts.clone()))
+            .collect();
+        let mut ungrouped: Vec<String> = self
+            .posts
+            .keys()
+            .filter(|k| !self.post_groups.values().any(|g| g.posts.contains(k)))
+            .cloned()
+            .collect();
+        if !ungrouped.is_empty() {
+            ungrouped.sort();
+            ordered.push((UNGROUPED_POSTS.into(), ungrouped));
+        }
+        ordered
+    }
+
+    /// returns the first account per post, i.e. the first account of its
+    /// account codes (ranges and prefixes included) that is mapped to the post
+    pub(super) fn get_first_account_per_post(&self) -> Vec<String> {
+        self.posts
+            .iter()
+            .filter_map(|(key, post)| {
+                post.account_codes
+                    .iter()
+                    .find_map(|pattern| self.get_first_account_of_pattern(key, pattern))
+            })
+            .collect()
+    }
+
+    /// the smallest account covered by the `pattern` of the post `key` that is mapped to the post,
+    /// i.e. not shadowed by a more specific pattern of another post.
+    /// As the post of the accounts only changes at the bounds of the patterns,
+    /// the candidates are the lower bounds of the patterns and the accounts following their upper bounds.
+    fn get_first_account_of_pattern(&self, key: &str, pattern: &AccountPattern) -> Option<String> {
+        let length = pattern.account_length();
+        let (from, to) = pattern.interval(length)?;
+        let mut candidates: Vec<u128> = self
+            .posts
+            .values()
+            .flat_map(|p| &p.account_codes)
+            .filter_map(|p| p.interval(length))
+            .flat_map(|(lower, upper)| [lower, upper + 1])
+            .filter(|c| (from..=to).contains(c))
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+            .into_iter()
+            .map(|c| format!("{c:0length$}"))
+            .find(|a| self.get_post_key_by_account(a).as_deref() == Some(key))
+    }
+
+    /// Ensures the single accounts of the posts are considered by the closing,
+    /// i.e. match the regular expression `include` of the `[close]` section.
+    fn validate_account_codes(&self) -> Result<(), Box<dyn Error>> {
+        let mut accounts: Vec<(&str, &str)> = self
+            .posts
+            .iter()
+            .flat_map(|(key, post)| {
+                post.account_codes
+                    .iter()
+                    .filter_map(move |code| match code {
+                        AccountPattern::Exact(account) => Some((key.as_str(), account.as_str())),
+                        _ => None,
+                    })
+            })
+            .collect();
+        accounts.sort_unstable();
+        let (keys, codes): (Vec<&str>, Vec<&str>) = accounts.into_iter().unzip();
+        let not_included = df!("Post" => keys, "Account" => codes)?
+            .lazy()
+            .filter(not(col("Account")
+                .str()
+                .contains(lit(self.close.include.clone()), false)))
+            .collect()?;
+        match not_included.height() {
+            0 => Ok(()),
+            _ => Err(format!(
+                "Accounts of posts not matching the accounts of the closing ({}): {not_included}",
+                self.close.include
+            )
+            .into()),
+        }
+    }
+}
+
+pub fn read_budget_config(budget_config_file: &Path) -> Result<Budget, Box<dyn Error>> {
+    let toml_str = fs::read_to_string(budget_config_file)?;
+    let budget: Budget = toml::from_str(&toml_str)?;
+    budget.validate_account_codes()?;
+    Ok(budget)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    fn read_budget_from_samples() -> Budget {
+        let config_file = &PathBuf::from("samples/budget.toml");
+        let budget = read_budget_config(config_file);
+        let Ok(budget) = budget else {
+            panic!("Invalid budget: {budget:#?}");
+        };
+        budget
+    }
+
+    #[rstest]
+    fn can_read_from_sample_config_file() {
+        let budget = read_budget_from_samples();
+        let Some(post) = budget.get_post_by_account("30100") else {
+            panic!("Account 30100 not found in budget");
+        };
+        assert_eq!("Ertrag Restauration", post.name);
+    }
+
+    #[rstest]
+    fn sample_config_file_uses_default_close_config() {
+        let budget = read_budget_from_samples();
+        assert_eq!(budget.close.include, r"^[3456789]\d{3,4}$");
+        assert!(budget.is_excluded("8900"));
+        assert!(!budget.is_excluded("89000"));
+        assert!(budget.is_transitory("10920"));
+        assert!(!budget.is_transitory("10200"));
+        assert!((budget.close.large_amount - 10_000.0).abs() < f64::EPSILON);
+    }
+
+    #[rstest]
+    fn can_read_close_config() {
+        let budget: Budget = toml::from_str(
+            r#"
+name = "Close"
+post_groups = {}
+posts = {}
+years = {}
+
+[close]
+include = '^[3-8]\d{4}$'
+exclude = ["89*", "80500"]
+transitory = ["109*"]
+large_amount = 2500.0
+"#,
+        )
+        .expect("valid budget");
+        assert_eq!(budget.close.include, r"^[3-8]\d{4}$");
+        assert!(budget.is_excluded("89100"));
+        assert!(budget.is_excluded("80500"));
+        assert!(!budget.is_excluded("80501"));
+        assert!(budget.is_transitory("10950"));
+        assert!(!budget.is_transitory("20051"));
+        assert!((budget.close.large_amount - 2500.0).abs() < f64::EPSILON);
+    }
+
+    #[rstest]
+    #[case("E30", 0..5, Ok(5.0 / 12.0))]
+    #[case("E31", 0..5, Ok(0.5))]
+    #[case("E31", 0..12, Ok(1.0))]
+    #[case("E31", 9..12, Ok(0.5))]
+    #[case("E320", 0..6, Ok(0.75))]
+    #[case("E320", 6..7, Ok(0.25))]
+    #[case("E33", 0..6, Err("Unknown phasing profile winter of post E33".to_string()))]
+    #[case(
+        "E36",
+        0..6,
+        Err("Invalid phasing of post E36: expected 12 monthly weights, found 2".to_string())
+    )]
+    fn test_get_phasing_share(
+        #[case] post_key: &str,
+        #[case] months: Range<usize>,
+        #[case] expected: Result<f64, String>,
+    ) {
+        let budget: Budget = toml::from_str(
+            r#"
+name = "Phasing"
+post_groups = {}
+years = {}
+
+[phasing_profiles]
+summer_party = [0, 0, 0, 0, 0, 3, 1, 0, 0, 0, 0, 0]
+
+[posts.E30]
+name = "Ertrag Restauration"
+sort = 1
+factor = -1
+account_codes = ["30100"]
+
+[posts.E31]
+name = "Ertrag Vermietungen"
+sort = 2
+factor = -1
+account_codes = ["31000"]
+phasing = [1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 5]
+
+[posts.E320]
+name = "Kulturelle Einnahmen"
+sort = 3
+factor = -1
+account_codes = ["32000"]
+phasing = "summer_party"
+
+[posts.E33]
+name = "Mitgliederbeiträge"
+sort = 4
+factor = -1
+account_codes = ["35000"]
+phasing = "winter"
+
+[posts.E36]
+name = "Spenden"
+sort = 5
+factor = -1
+account_codes = ["36000"]
+phasing = [1, 2]
+"#,
+        )
+        .expect("valid budget");
+        assert_eq!(budget.get_phasing_share(post_key, months), expected);
+    }
+
+    #[rstest]
+    #[case("30100", Some("E30"))]
+    #[case("30150", Some("E3X"))]
+    #[case("30999", Some("E3X"))]
+    #[case("30500", Some("E30"))]
+    #[case("68450", Some("A684"))]
+    #[case("68100", Some("A68"))]
+    #[case("31000", None)]
+    fn can_get_post_by_ranges_and_prefixes(#[case] account: &str, #[case] expected: Option<&str>) {
+        let budget: Budget = toml::from_str(
+            r#"
+name = "Patterns"
+post_groups = {}
+years = {}
+
+[posts.E30]
+name = "Ertrag Restauration"
+sort = 1
+factor = -1
+account_codes = ["30100", "30500"]
+
+[posts.E3X]
+name = "Ertrag Diverses"
+sort = 2
+factor = -1
+account_codes = ["30100-30999"]
+
+[posts.A68]
+name = "Finanzaufwand"
+sort = 3
+factor = 1
+account_codes = ["68*"]
+
+[posts.A684]
+name = "Bankspesen"
+sort = 4
+factor = 1
+account_codes = ["68400-68499"]
+"#,
+        )
+        .expect("valid budget");
+        assert_eq!(budget.get_post_key_by_account(account).as_deref(), expected);
+        let mut dummy_accounts = budget.get_first_account_per_post();
+        dummy_accounts.sort();
+        assert_eq!(dummy_accounts, vec!["30100", "30101", "68000", "68400"]);
+    }
+
+    #[rstest]
+    fn can_get_first_account_of_shadowed_prefix() {
+        let budget: Budget = toml::from_str(
+            r#"
+name = "Patterns"
+post_groups = {}
+years = {}
+
+[posts.A6]
+name = "Übriger Aufwand"
+sort = 1
+factor = 1
+account_codes = ["6*"]
+
+[posts.A60]
+name = "Raumaufwand"
+sort = 2
+factor = 1
+account_codes = ["60000-67999", "68000"]
+
+[posts.A7]
+name = "Nebenbetrieb"
+sort = 3
+factor = 1
+account_codes = ["7*"]
+
+[posts.A70]
+name = "Nebenbetrieb Total"
+sort = 4
+factor = 1
+account_codes = ["70000-79999"]
+"#,
+        )
+        .expect("valid budget");
+        let mut dummy_accounts = budget.get_first_account_per_post();
+        dummy_accounts.sort();
+        assert_eq!(dummy_accounts, vec!["60000", "68001", "70000"]);
+    }
+
+    #[rstest]
+    #[case("include = '^[3-8]\\d{4}$'", "90000")]
+    #[case("", "301000")]
+    fn rejects_account_codes_not_matching_include(#[case] close: &str, #[case] account: &str) {
+        let budget: Budget = toml::from_str(&format!(
+            r#"
+name = "Patterns"
+post_groups = {{}}
+years = {{}}
+
+[close]
+{close}
+
+[posts.E30]
+name = "Ertrag Restauration"
+sort = 1
+factor = -1
+account_codes = ["30100", "{account}"]
+"#
+        ))
+        .expect("valid budget");
+        let error = budget
+            .validate_account_codes()
+            .expect_err("account not matching include");
+        assert!(error.to_string().contains(account), "{error}");
+    }
+
+    #[rstest]
+    fn can_select_scenario() {
+        let budget = read_budget_from_samples();
+        let period = budget.fiscal_period("202505").expect("valid period");
+        assert_eq!(budget.label(&period), "Budget 2025");
+
+        let revised = budget
+            .clone()
+            .with_scenario("revised", "2025")
+            .expect("scenario is defined");
+        assert_eq!(revised.label(&period), "Budget 2025 revised");
+        let amount = |account, year| revised.get_budget_amount_by_account(account, year);
+        assert!((amount("30100", "2025") - 45.01).abs() < f64::EPSILON);
+        assert!((amount("40000", "2025") - 35.01).abs() < f64::EPSILON);
+        assert!(
+            (amount("31000", "2025") - 31.01).abs() < f64::EPSILON,
+            "amounts not in the scenario are kept"
+        );
+        assert!((amount("30100", "2024") - 30.0).abs() < f64::EPSILON);
+
+        let result = budget.with_scenario("revised", "2024");
+        assert_eq!(
+            result.expect_err("scenario not defined").to_string(),
+            "Scenario revised not found in year 2024 of the budget"
+        );
+    }
+
+    #[rstest]
+    fn can_get_dummy_posts() {
+        let budget = read_budget_from_samples();
+        let accounts = budget.get_first_account_per_post();
+        assert_ne!(accounts.len(), 0);
+    }
+}
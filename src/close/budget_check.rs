@@ -0,0 +1,384 @@
+use crate::close::account_pattern::{AccountPattern, Overlap};
+use crate::close::budget::{LINEAR, Phasing, validate_weights};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use toml::Spanned;
+
+/// Checks the budget configuration file for inconsistencies.
+/// Prints each issue found with a reference to the line in the configuration file
+/// and fails if there is at least one issue.
+pub fn check_budget(budget_config_file: &Path) -> Result<(), Box<dyn Error>> {
+    let toml_str = fs::read_to_string(budget_config_file)?;
+    let issues = find_issues(&toml_str)?;
+    let file_name = budget_config_file.display();
+    if issues.is_empty() {
+        println!("No issues found in {file_name}.");
+        Ok(())
+    } else {
+        for issue in &issues {
+            println!("{file_name}:{issue}");
+        }
+        Err(format!("Found {} issue(s) in {file_name}.", issues.len()).into())
+    }
+}
+
+/// An inconsistency in the budget configuration, located by its (one-based) line
+#[derive(Debug, PartialEq)]
+struct Issue {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.line, self.message)
+    }
+}
+
+/// The budget configuration with the position of the values to be checked
+#[derive(Deserialize, Debug)]
+struct BudgetSpans {
+    post_groups: HashMap<String, PostGroupSpans>,
+    posts: HashMap<String, PostSpans>,
+    years: HashMap<String, YearSpans>,
+    #[serde(default)]
+    phasing_profiles: HashMap<String, Spanned<Vec<f64>>>,
+    #[serde(default)]
+    cost_centres: HashMap<String, CostCentreSpans>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PostGroupSpans {
+    posts: Vec<Spanned<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PostSpans {
+    name: Spanned<String>,
+    account_codes: Vec<Spanned<String>>,
+    sort: Spanned<i64>,
+    factor: Spanned<i64>,
+    phasing: Option<Spanned<Phasing>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct YearSpans {
+    amounts: HashMap<String, Spanned<f64>>,
+    #[serde(default)]
+    scenarios: HashMap<String, ScenarioSpans>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ScenarioSpans {
+    amounts: HashMap<String, Spanned<f64>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CostCentreSpans {
+    #[serde(default)]
+    years: HashMap<String, YearSpans>,
+}
+
+/// Returns the issues found in the budget configuration, ordered by line.
+#[allow(clippy::too_many_lines)]
+fn find_issues(toml_str: &str) -> Result<Vec<Issue>, Box<dyn Error>> {
+    let budget: BudgetSpans = toml::from_str(toml_str)?;
+    let line = |offset: usize| toml_str[..offset].matches('\n').count() + 1;
+    let mut issues = Vec::new();
+
+    let mut posts_by_account: BTreeMap<&str, Vec<(&str, usize)>> = BTreeMap::new();
+    let mut posts_by_sort: BTreeMap<i64, Vec<(&str, usize)>> = BTreeMap::new();
+    let mut patterns: Vec<(&str, AccountPattern, usize)> = Vec::new();
+    for (key, post) in &budget.posts {
+        for code in &post.account_codes {
+            let code_line = line(code.span().start);
+            posts_by_account
+                .entry(code.get_ref())
+                .or_default()
+                .push((key, code_line));
+            match AccountPattern::try_from(code.get_ref().clone()) {
+                Ok(pattern) => patterns.push((key, pattern, code_line)),
+                Err(message) => issues.push(Issue {
+                    line: code_line,
+                    message,
+                }),
+            }
+        }
+        posts_by_sort
+            .entry(*post.sort.get_ref())
+            .or_default()
+            .push((key, line(post.sort.span().start)));
+        if let Some(phasing) = &post.phasing
+            && let Some(reason) = invalid_phasing(phasing.get_ref(), &budget.phasing_profiles)
+        {
+            issues.push(Issue {
+                line: line(phasing.span().start),
+                message: format!("Post {key} has an invalid phasing: {reason}"),
+            });
+        }
+        let factor = *post.factor.get_ref();
+        if factor.abs() != 1 {
+            issues.push(Issue {
+                line: line(post.factor.span().start),
+                message: format!("Post {key} has factor {factor}, expected 1 or -1"),
+            });
+        }
+        if !budget
+            .post_groups
+            .values()
+            .any(|g| g.posts.iter().any(|p| p.get_ref() == key))
+        {
+            issues.push(Issue {
+                line: line(post.name.span().start),
+                message: format!("Post {key} is not referenced by any post group"),
+            });
+        }
+    }
+    for (account, posts) in posts_by_account {
+        if let Some(message) = duplicates_message(posts) {
+            issues.push(Issue {
+                line: message.0,
+                message: format!(
+                    "Account {account} is assigned to multiple posts: {}",
+                    message.1
+                ),
+            });
+        }
+    }
+    issues.extend(find_overlapping_patterns(&patterns));
+    for (sort, posts) in posts_by_sort {
+        if let Some(message) = duplicates_message(posts) {
+            issues.push(Issue {
+                line: message.0,
+                message: format!("Sort {sort} is used by multiple posts: {}", message.1),
+            });
+        }
+    }
+
+    for (group_key, group) in &budget.post_groups {
+        for post in group
+            .posts
+            .iter()
+            .filter(|p| !budget.posts.contains_key(p.get_ref()))
+        {
+            issues.push(Issue {
+                line: line(post.span().start),
+                message: format!(
+                    "Post group {group_key} refers to unknown post {}",
+                    post.get_ref()
+                ),
+            });
+        }
+    }
+
+    for (name, weights) in &budget.phasing_profiles {
+        if let Err(reason) = validate_weights(weights.get_ref()) {
+            issues.push(Issue {
+                line: line(weights.span().start),
+                message: format!("Phasing profile {name} is invalid: {reason}"),
+            });
+        }
+    }
+
+    for (year, amounts) in &budget.years {
+        for (key, amount) in &amounts.amounts {
+            if !budget.posts.contains_key(key) {
+                issues.push(Issue {
+                    line: line(amount.span().start),
+                    message: format!("Amount {key} of year {year} does not refer to a post"),
+                });
+            }
+        }
+        for (scenario_name, scenario) in &amounts.scenarios {
+            for (key, amount) in &scenario.amounts {
+                if !budget.posts.contains_key(key) {
+                    issues.push(Issue {
+                        line: line(amount.span().start),
+                        message: format!(
+                            "Amount {key} of scenario {scenario_name} of year {year} does not refer to a post"
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    for (code, cost_centre) in &budget.cost_centres {
+        for (year, amounts) in &cost_centre.years {
+            for (key, amount) in &amounts.amounts {
+                if !budget.posts.contains_key(key) {
+                    issues.push(Issue {
+                        line: line(amount.span().start),
+                        message: format!(
+                            "Amount {key} of year {year} of cost centre {code} does not refer to a post"
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| (a.line, &a.message).cmp(&(b.line, &b.message)));
+    Ok(issues)
+}
+
+/// Returns the reason if the phasing of a post is invalid
+fn invalid_phasing<T>(phasing: &Phasing, profiles: &HashMap<String, T>) -> Option<String> {
+    match phasing {
+        Phasing::Weights(weights) => validate_weights(weights).err(),
+        Phasing::Profile(name) if name != LINEAR && !profiles.contains_key(name) => {
+            Some(format!("unknown phasing profile {name}"))
+        }
+        Phasing::Profile(_) => None,
+    }
+}
+
+/// Returns the issues for account patterns of different posts
+/// covering the same accounts or overlapping partially.
+/// Nested patterns are fine, as the more specific one takes precedence.
+/// Identical account codes are reported separately.
+fn find_overlapping_patterns(patterns: &[(&str, AccountPattern, usize)]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (i, (key_a, a, line_a)) in patterns.iter().enumerate() {
+        for (key_b, b, line_b) in &patterns[i + 1..] {
+            if key_a == key_b || a == b {
+                continue;
+            }
+            let relation = match a.overlap(b) {
+                Overlap::Identical => "cover the same accounts",
+                Overlap::Partial => "overlap partially",
+                Overlap::Disjoint | Overlap::Nested => continue,
+            };
+            let ((key_a, a, line_a), (key_b, b, line_b)) = if line_a <= line_b {
+                ((key_a, a, line_a), (key_b, b, line_b))
+            } else {
+                ((key_b, b, line_b), (key_a, a, line_a))
+            };
+            issues.push(Issue {
+                line: *line_a,
+                message: format!(
+                    "Account codes {a} of {key_a} (line {line_a}) and {b} of {key_b} (line {line_b}) {relation}"
+                ),
+            });
+        }
+    }
+    issues
+}
+
+/// If more than one post is listed, returns the first line and
+/// the posts with their lines (e.g. `E30 (line 12), E31 (line 20)`).
+fn duplicates_message(mut posts: Vec<(&str, usize)>) -> Option<(usize, String)> {
+    if posts.len() < 2 {
+        return None;
+    }
+    posts.sort_by_key(|(_, line)| *line);
+    let listing = posts
+        .iter()
+        .map(|(key, line)| format!("{key} (line {line})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some((posts[0].1, listing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const INCONSISTENT_BUDGET: &str = r#"name = "Inconsistent"
+
+[post_groups.revenue]
+name = "Ertrag"
+posts = ["E30", "E99"]
+
+[posts.E30]
+name = "Ertrag Restauration"
+sort = 1
+factor = -1
+account_codes = ["30100", "30200"]
+
+[posts.E31]
+name = "Ertrag Vermietungen"
+sort = 1
+factor = 2
+account_codes = ["30200", "30500-30599", "32*"]
+
+[posts.E32]
+name = "Kulturelle Einnahmen"
+sort = 3
+factor = -1
+account_codes = ["30550-30649", "32000-32999", "34-33"]
+
+[years.2025.amounts]
+E30 = 30.0
+E39 = 39.0
+
+[posts.E33]
+name = "Mitgliederbeiträge"
+sort = 4
+factor = -1
+account_codes = ["35000"]
+phasing = "winter"
+
+[posts.E36]
+name = "Spenden"
+sort = 5
+factor = -1
+account_codes = ["36000"]
+phasing = [1, 2]
+
+[phasing_profiles]
+summer = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+
+[cost_centres.SOMMERFEST]
+name = "Sommerfest"
+
+[cost_centres.SOMMERFEST.years.2025.amounts]
+E30 = 500.0
+E98 = 98.0
+
+[years.2025.scenarios.revised.amounts]
+E30 = 35.0
+E97 = 97.0
+"#;
+
+    #[rstest]
+    fn sample_budget_has_no_issues() {
+        let toml_str =
+            fs::read_to_string("samples/budget.toml").expect("can read sample budget config");
+        let issues = find_issues(&toml_str).expect("can check sample budget config");
+        assert_eq!(issues, vec![]);
+    }
+
+    #[rstest]
+    fn can_find_issues() {
+        let issues = find_issues(INCONSISTENT_BUDGET).expect("can check budget config");
+        let actual: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            actual,
+            vec![
+                "5: Post group revenue refers to unknown post E99",
+                "9: Sort 1 is used by multiple posts: E30 (line 9), E31 (line 15)",
+                "11: Account 30200 is assigned to multiple posts: E30 (line 11), E31 (line 17)",
+                "14: Post E31 is not referenced by any post group",
+                "16: Post E31 has factor 2, expected 1 or -1",
+                "17: Account codes 30500-30599 of E31 (line 17) and 30550-30649 of E32 (line 23) overlap partially",
+                "17: Account codes 32* of E31 (line 17) and 32000-32999 of E32 (line 23) cover the same accounts",
+                "20: Post E32 is not referenced by any post group",
+                "23: Invalid account range '34-33': lower bound exceeds upper bound",
+                "27: Amount E39 of year 2025 does not refer to a post",
+                "30: Post E33 is not referenced by any post group",
+                "34: Post E33 has an invalid phasing: unknown phasing profile winter",
+                "37: Post E36 is not referenced by any post group",
+                "41: Post E36 has an invalid phasing: expected 12 monthly weights, found 2",
+                "44: Phasing profile summer is invalid: the sum of the monthly weights must be positive",
+                "51: Amount E98 of year 2025 of cost centre SOMMERFEST does not refer to a post",
+                "55: Amount E97 of scenario revised of year 2025 does not refer to a post",
+            ]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::close::budget::CloseConfig;
+use crate::close::close_xml::{account_descriptions, get_balances_from, read_checked_xml};
+use crate::derive_month_from_accounts;
+use crate::money::zero;
+use polars::prelude::*;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The post groups of the skeleton per account class (first digit of the account)
+const ACCOUNT_CLASSES: [(char, &str, &str); 7] = [
+    ('3', "revenue", "Betriebsertrag"),
+    ('4', "costs_direct", "Aufwand für Material und Waren"),
+    ('5', "costs_staff", "Personalaufwand"),
+    ('6', "costs_remaining", "Übriger Betriebsaufwand"),
+    ('7', "side_results", "Betrieblicher Nebenerfolg"),
+    (
+        '8',
+        "extraordinary",
+        "Ausserordentlicher Aufwand und Ertrag",
+    ),
+    ('9', "closing", "Abschluss"),
+];
+
+/// Creates a skeleton of the budget configuration for the year following the one of the accounts file
+/// and writes it to `budget_<year>_<ts>.toml`.
+pub fn init_budget(accounts_file: &Path, ts: &str) -> Result<(), Box<dyn Error>> {
+    let extension = accounts_file
+        .extension()
+        .and_then(|e| e.to_str())
+        .ok_or("No valid file extension found")?;
+    if extension != "xls" {
+        return Err(format!("File extension {extension} is not supported.").into());
+    }
+    let month = derive_month_from_accounts(accounts_file.as_os_str().to_str(), extension)?;
+    let year = month[0..4].parse::<i32>()? + 1;
+    let (journal, accounts) = read_checked_xml(accounts_file)?;
+    let skeleton = budget_skeleton(&journal, &accounts, &year.to_string())?;
+    let path = PathBuf::from(format!("budget_{year}_{ts}.toml"));
+    fs::write(&path, skeleton)?;
+    println!("Written budget skeleton to {}.", path.display());
+    Ok(())
+}
+
+/// Derives a budget configuration in TOML format with one post per account considered in the closing
+/// (the defaults of `[close]`), taken from the `accounts` and the `journal`.
+/// The posts are sorted by account and grouped by account class, revenue accounts (`3xxx`) get factor `-1`.
+/// The balances of the `journal` (signed by the factor) are proposed as amounts of the `year`.
+fn budget_skeleton(
+    journal: &DataFrame,
+    accounts: &DataFrame,
+    year: &str,
+) -> Result<String, Box<dyn Error>> {
+    let config = CloseConfig::default();
+    let balances = get_balances_from(journal, &config.include, lit(true))?;
+    let posts = account_descriptions(accounts)
+        .join(
+            balances.lazy(),
+            [col("Account")],
+            [col("Account")],
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .filter(
+            col("Account")
+                .str()
+                .contains(lit(config.include.clone()), false),
+        )
+        .with_columns([
+            col("Description").fill_null(col("Account")),
+            col("Balance").fill_null(zero()).cast(DataType::Float64),
+        ])
+        .sort(["Account"], SortMultipleOptions::default())
+        .collect()?;
+
+    let posts: Vec<SkeletonPost> = posts
+        .column("Account")?
+        .str()?
+        .into_no_null_iter()
+        .zip(posts.column("Description")?.str()?.into_no_null_iter())
+        .zip(posts.column("Balance")?.f64()?.into_no_null_iter())
+        .filter(|((account, _), _)| !config.exclude.iter().any(|p| p.matches(account)))
+        .zip(1..)
+        .map(|(((account, name), balance), sort)| SkeletonPost::new(account, name, balance, sort))
+        .collect();
+
+    let mut skeleton = format!("name = \"Budget {year}\"\n\n[post_groups]\n");
+    for (class, key, name) in ACCOUNT_CLASSES {
+        let keys: Vec<&str> = posts
+            .iter()
+            .filter(|p| p.account.starts_with(class))
+            .map(|p| p.key.as_str())
+            .collect();
+        if !keys.is_empty() {
+            write!(
+                skeleton,
+                "\n[post_groups.{key}]\nname = \"{name}\"\nposts = {}\n",
+                toml::Value::from(keys)
+            )?;
+        }
+    }
+    skeleton += "\n[posts]\n";
+    for post in &posts {
+        write!(
+            skeleton,
+            "\n[posts.{}]\nname = {}\nsort = {}\nfactor = {}\naccount_codes = [\"{}\"]\n",
+            post.key,
+            toml::Value::from(post.name.as_str()),
+            post.sort,
+            post.factor,
+            post.account
+        )?;
+    }
+    write!(
+        skeleton,
+        "\n[years]\n\n[years.{year}]\n\n[years.{year}.amounts]\n"
+    )?;
+    for post in &posts {
+        writeln!(skeleton, "{} = {:.2}", post.key, post.amount)?;
+    }
+    Ok(skeleton)
+}
+
+/// A post of the budget skeleton, covering a single account
+struct SkeletonPost {
+    key: String,
+    account: String,
+    name: String,
+    sort: i64,
+    factor: i64,
+    amount: f64,
+}
+
+impl SkeletonPost {
+    /// Revenue accounts (`3xxx`) become posts `E<account>` with factor `-1`,
+    /// all others posts `A<account>` with factor `1`. The `balance` signed by the factor is the amount.
+    fn new(account: &str, name: &str, balance: f64, sort: i64) -> Self {
+        let (prefix, factor, sign) = if account.starts_with('3') {
+            ("E", -1, -1.0)
+        } else {
+            ("A", 1, 1.0)
+        };
+        SkeletonPost {
+            key: format!("{prefix}{account}"),
+            account: account.into(),
+            name: name.into(),
+            sort,
+            factor,
+            // adding 0.0 turns -0.0 into 0.0
+            amount: (balance * sign * 100.0).round() / 100.0 + 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close::account_pattern::AccountPattern;
+    use crate::close::budget::Budget;
+    use crate::test_fixtures::{accounts_df_01, journal_df_01};
+    use rstest::rstest;
+
+    #[rstest]
+    fn can_derive_budget_skeleton(journal_df_01: DataFrame, accounts_df_01: DataFrame) {
+        let skeleton =
+            budget_skeleton(&journal_df_01, &accounts_df_01, "2026").expect("can derive skeleton");
+        let budget: Budget = toml::from_str(&skeleton).expect("skeleton is a valid budget");
+
+        let revenue = &budget.post_groups["revenue"];
+        assert_eq!(revenue.name, "Betriebsertrag");
+        assert_eq!(revenue.posts, vec!["E30100", "E30700", "E31000", "E36000"]);
+        assert!(!budget.post_groups.contains_key("costs_direct"));
+
+        let post = &budget.posts["E30100"];
+        assert_eq!(post.name, "Ertrag Café");
+        assert_eq!((post.sort, post.factor), (1, -1));
+        assert_eq!(
+            post.account_codes,
+            vec![AccountPattern::Exact("30100".into())]
+        );
+        assert!(!budget.posts.contains_key("A8900"));
+
+        let amounts = &budget.years["2026"].amounts;
+        assert_eq!(
+            amounts.get("E30100").copied(),
+            Some(900.0),
+            "revenue is proposed positive"
+        );
+        assert_eq!(amounts.get("E30700").copied(), Some(200.0));
+        assert_eq!(amounts.get("E31000").copied(), Some(100.0));
+        assert_eq!(amounts.get("E36000").copied(), Some(-200.0));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::close::as_dataframe;
+use crate::close::budget::{Budget, Post, read_budget_config};
+use crate::close::close_xml::{aggregate_balances, read_checked_xml};
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::derive_month_from_accounts;
+use crate::export::path_with_prefix;
+use polars::prelude::*;
+use polars_excel_writer::PolarsExcelWriter;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// The name of the worksheet comparing the proposal with the current budget
+const PROPOSAL: &str = "vorschlag";
+
+/// The adjustments applied to the figures of the current year to propose the budget of the next year
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Adjustments {
+    /// The figures the proposal is based on, unless overridden per post
+    base: Base,
+    /// The growth in percent applied to the base, unless overridden per post
+    growth: f64,
+    /// Adjustments of single posts, keyed by the post
+    posts: HashMap<String, PostAdjustment>,
+}
+
+/// The figures of the current year a proposal is based on
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Base {
+    /// The budget of the current year
+    Budget,
+    /// The actual figures up to and including the closing month
+    Actual,
+    /// The actual figures extrapolated to the whole year
+    #[default]
+    Forecast,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct PostAdjustment {
+    base: Option<Base>,
+    growth: Option<f64>,
+    /// A fixed amount, taking precedence over base and growth
+    amount: Option<f64>,
+}
+
+/// Proposes the budget of the year following the fiscal year of the accounts file,
+/// based on the budget, the actual figures and the forecast of the current year
+/// adjusted by the optional `adjustments_file`.
+/// Writes a copy of the budget configuration with the proposed year added
+/// and a spreadsheet comparing the proposal with the figures of the current year.
+pub fn propose_budget(
+    budget_config_file: &Path,
+    accounts_file: &Path,
+    adjustments_file: Option<&Path>,
+    ts: &str,
+) -> Result<(), Box<dyn Error>> {
+    let extension = accounts_file
+        .extension()
+        .and_then(|e| e.to_str())
+        .ok_or("No valid file extension found")?;
+    if extension != "xls" {
+        return Err(format!("File extension {extension} is not supported.").into());
+    }
+    let month = derive_month_from_accounts(accounts_file.as_os_str().to_str(), extension)?;
+    let budget = read_budget_config(budget_config_file)?;
+    let adjustments = match adjustments_file {
+        Some(path) => toml::from_str(&fs::read_to_string(path)?)?,
+        None => Adjustments::default(),
+    };
+    let period = budget.fiscal_period(&month)?;
+    let next_year = (period.budget_year().parse::<i32>()? + 1).to_string();
+
+    let (journal, _) = read_checked_xml(accounts_file)?;
+    let dummy_accounts = as_dataframe(budget.get_first_account_per_post(), &period.first_date())?;
+    let extended = journal.vstack(&dummy_accounts)?;
+    let aggregated = aggregate_balances(&extended, None, &budget, &period)?;
+    let proposal = propose(&aggregated, &budget, &period, &adjustments, &next_year)?;
+
+    let toml_str = fs::read_to_string(budget_config_file)?;
+    let proposed_budget = with_proposed_year(&toml_str, &budget, &proposal, &next_year)?;
+    let path = path_with_prefix("budget_proposal", &next_year, ts);
+    fs::write(path.with_extension("toml"), proposed_budget)?;
+    write_proposal_to_file(&proposal, &path)?;
+    println!(
+        "Written budget proposal to {} and {}.",
+        path.with_extension("toml").display(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Lists the posts of the `budget` with their key, the budget, the actual figures and the forecast
+/// of the current year up to the `period` (as in the closing report, taken from the `aggregated` figures)
+/// and the amount proposed for the `next_year`, rounded to two decimals.
+/// Posts without figures are skipped, posts sharing their name are rejected,
+/// as their figures cannot be told apart.
+fn propose(
+    aggregated: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+    adjustments: &Adjustments,
+    next_year: &str,
+) -> Result<DataFrame, Box<dyn Error>> {
+    if let Some(unknown) = adjustments
+        .posts
+        .keys()
+        .find(|k| !budget.posts.contains_key(*k))
+    {
+        return Err(format!("Adjustment of post {unknown} does not refer to a post").into());
+    }
+    let mut posts: Vec<(&String, &Post)> = budget.posts.iter().collect();
+    posts.sort_by(|(k1, p1), (k2, p2)| p1.sort.cmp(&p2.sort).then(k1.cmp(k2)));
+    let mut keys_by_name: HashMap<&str, &str> = HashMap::new();
+    for (key, post) in &posts {
+        if let Some(other) = keys_by_name.insert(post.name.as_str(), key.as_str()) {
+            return Err(format!("Posts {other} and {key} share the name {}", post.name).into());
+        }
+    }
+
+    let (budget_alias, actual_alias) = (budget.label(period), period.period_label());
+    let amounts = |column: &str| -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        Ok(aggregated
+            .column(column)?
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .collect())
+    };
+    let figures: HashMap<&str, [Option<f64>; 3]> = aggregated
+        .column("Group")?
+        .str()?
+        .into_no_null_iter()
+        .zip(amounts(&budget_alias)?)
+        .zip(amounts(&actual_alias)?)
+        .zip(amounts("Prognose")?)
+        .map(|(((name, current), actual), forecast)| (name, [current, actual, forecast]))
+        .collect();
+
+    let (mut keys, mut names, mut proposed) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut currents, mut actuals, mut forecasts) = (Vec::new(), Vec::new(), Vec::new());
+    for (key, post) in posts {
+        let Some(&[current, actual, forecast]) = figures.get(post.name.as_str()) else {
+            continue;
+        };
+        let adjustment = adjustments.posts.get(key);
+        let amount = if let Some(amount) = adjustment.and_then(|a| a.amount) {
+            amount
+        } else {
+            let base = match adjustment.and_then(|a| a.base).unwrap_or(adjustments.base) {
+                Base::Budget => current,
+                Base::Actual => actual,
+                Base::Forecast => forecast,
+            };
+            let growth = adjustment
+                .and_then(|a| a.growth)
+                .unwrap_or(adjustments.growth);
+            base.unwrap_or(0.0) * (1.0 + growth / 100.0)
+        };
+        keys.push(key.as_str());
+        names.push(post.name.as_str());
+        currents.push(current);
+        actuals.push(actual);
+        forecasts.push(forecast);
+        // adding 0.0 turns -0.0 into 0.0
+        proposed.push((amount * 100.0).round() / 100.0 + 0.0);
+    }
+
+    let proposal = df!(
+        "Post Key" => keys,
+        "Post" => names,
+        &budget_alias => currents,
+        &actual_alias => actuals,
+        "Prognose" => forecasts,
+        &format!("Vorschlag {next_year}") => proposed,
+    )?
+    .lazy()
+    .with_column(
+        (col(format!("Vorschlag {next_year}")) - col(&budget_alias))
+            .round(2, RoundMode::HalfToEven)
+            .alias("Veränderung"),
+    )
+    .collect()?;
+    Ok(proposal)
+}
+
+/// Appends the amounts of the `proposal` as `[years.<next_year>]` to the budget configuration,
+/// failing if the budget already contains that year.
+fn with_proposed_year(
+    toml_str: &str,
+    budget: &Budget,
+    proposal: &DataFrame,
+    next_year: &str,
+) -> Result<String, Box<dyn Error>> {
+    if budget.years.contains_key(next_year) {
+        return Err(format!("The budget already contains the year {next_year}").into());
+    }
+    let mut proposed = toml_str.trim_end().to_string();
+    write!(
+        proposed,
+        "\n\n[years.{next_year}]\n\n[years.{next_year}.amounts]\n"
+    )?;
+    for (key, amount) in proposal.column("Post Key")?.str()?.into_no_null_iter().zip(
+        proposal
+            .column(&format!("Vorschlag {next_year}"))?
+            .f64()?
+            .into_no_null_iter(),
+    ) {
+        writeln!(proposed, "{key} = {amount:.2}")?;
+    }
+    Ok(proposed)
+}
+
+fn write_proposal_to_file(proposal: &DataFrame, path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut excel_writer = PolarsExcelWriter::new();
+    excel_writer.set_autofit(true);
+    excel_writer.set_dtype_float_format("#'##0.00");
+    excel_writer.set_freeze_panes(1, 2);
+    excel_writer.set_worksheet_name(PROPOSAL)?;
+    excel_writer.write_dataframe(proposal)?;
+    excel_writer.save(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::aggregated_df_01_202505;
+    use crate::test_utils::assert_dataframe;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    fn read_sample_budget() -> Budget {
+        read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget")
+    }
+
+    fn period_202505() -> FiscalPeriod {
+        read_sample_budget()
+            .fiscal_period("202505")
+            .expect("valid period")
+    }
+
+    #[rstest]
+    fn can_propose_budget(aggregated_df_01_202505: DataFrame) {
+        let adjustments: Adjustments = toml::from_str(
+            r#"
+            growth = 10.0
+
+            [posts.E30]
+            base = "budget"
+            growth = 0.0
+
+            [posts.E360]
+            amount = 500.0
+            "#,
+        )
+        .expect("valid adjustments");
+        let expected = df!(
+            "Post Key" => ["E30", "E360"],
+            "Post" => ["Ertrag Restauration", "Spenden"],
+            "Budget 2025" => [30.01, 360.01],
+            "1.1.-31.5.25" => [1200.0, -200.0],
+            "Prognose" => [2880.0, -480.0],
+            "Vorschlag 2026" => [30.01, 500.0],
+            "Veränderung" => [0.0, 139.99],
+        )
+        .expect("valid proposal");
+
+        let actual = propose(
+            &aggregated_df_01_202505,
+            &read_sample_budget(),
+            &period_202505(),
+            &adjustments,
+            "2026",
+        )
+        .expect("can propose budget");
+        assert_dataframe(&actual, &expected);
+
+        let forecast = propose(
+            &aggregated_df_01_202505,
+            &read_sample_budget(),
+            &period_202505(),
+            &Adjustments {
+                growth: 10.0,
+                ..Adjustments::default()
+            },
+            "2026",
+        )
+        .expect("can propose budget");
+        assert_eq!(
+            forecast
+                .column("Vorschlag 2026")
+                .expect("proposal column")
+                .f64()
+                .expect("proposed amounts")
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec![3168.0, -528.0]
+        );
+    }
+
+    #[rstest]
+    fn rejects_adjustment_of_unknown_post(aggregated_df_01_202505: DataFrame) {
+        let adjustments: Adjustments =
+            toml::from_str("[posts.X99]\namount = 1.0").expect("valid adjustments");
+        let result = propose(
+            &aggregated_df_01_202505,
+            &read_sample_budget(),
+            &period_202505(),
+            &adjustments,
+            "2026",
+        );
+        assert_eq!(
+            result.expect_err("unknown post").to_string(),
+            "Adjustment of post X99 does not refer to a post"
+        );
+    }
+
+    #[rstest]
+    fn rejects_posts_sharing_their_name(aggregated_df_01_202505: DataFrame) {
+        let mut budget = read_sample_budget();
+        budget.posts.get_mut("E31").expect("sample post E31").name = "Spenden".into();
+        let result = propose(
+            &aggregated_df_01_202505,
+            &budget,
+            &period_202505(),
+            &Adjustments::default(),
+            "2026",
+        );
+        assert_eq!(
+            result.expect_err("shared name").to_string(),
+            "Posts E31 and E360 share the name Spenden"
+        );
+    }
+
+    #[rstest]
+    fn can_add_proposed_year(aggregated_df_01_202505: DataFrame) {
+        let toml_str = fs::read_to_string("samples/budget.toml").expect("readable sample budget");
+        let budget = read_sample_budget();
+        let proposal = propose(
+            &aggregated_df_01_202505,
+            &budget,
+            &period_202505(),
+            &Adjustments::default(),
+            "2026",
+        )
+        .expect("can propose budget");
+
+        let proposed =
+            with_proposed_year(&toml_str, &budget, &proposal, "2026").expect("can add year");
+        let proposed: Budget = toml::from_str(&proposed).expect("proposal is a valid budget");
+        let amounts = &proposed.years["2026"].amounts;
+        assert_eq!(amounts.get("E30").copied(), Some(2880.0));
+        assert_eq!(amounts.get("E360").copied(), Some(-480.0));
+        assert_eq!(proposed.years["2025"].amounts, budget.years["2025"].amounts);
+
+        let result = with_proposed_year(&toml_str, &budget, &proposal, "2025");
+        assert_eq!(
+            result.expect_err("existing year").to_string(),
+            "The budget already contains the year 2025"
+        );
+    }
+}
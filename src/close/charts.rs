@@ -0,0 +1,207 @@
+use crate::close::budget::Budget;
+use crate::close::close_xml::{RowType, excluded_accounts, get_name_of_post, post_group_mapping};
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::money::zero;
+use polars::prelude::*;
+use std::error::Error;
+use std::sync::Arc;
+
+/// The data the charts of the closing workbook are built from
+pub struct ChartData {
+    /// Budget and figures of the period per post group (`Post Group`, `Budget` and the period, e.g. `1.1.-31.5.25`)
+    pub groups: DataFrame,
+    /// Net result per month and cumulated (`Month`, `Ergebnis`, `Kumuliert`)
+    pub monthly: DataFrame,
+    /// Revenue of the period per post (`Post` and the period, e.g. `1.1.-31.5.25`)
+    pub revenue: DataFrame,
+}
+
+/// Derives the chart data from the `summary` arranged by post groups,
+/// the `aggregated` figures per post and the `journal`.
+pub fn chart_data(
+    journal: &DataFrame,
+    aggregated: &DataFrame,
+    summary: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<ChartData, Box<dyn Error>> {
+    Ok(ChartData {
+        groups: group_totals(summary, budget, period)?,
+        monthly: monthly_result(journal, budget, period)?,
+        revenue: revenue_by_post(aggregated, budget, period)?,
+    })
+}
+
+/// Takes the budget and the figures of the `period` of the subtotal rows.
+fn group_totals(
+    summary: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let groups = summary
+        .clone()
+        .lazy()
+        .filter(col("Row Type").eq(lit(RowType::Subtotal.to_string())))
+        .select([
+            col("Group")
+                .str()
+                .strip_prefix(lit("Total "))
+                .alias("Post Group"),
+            col(budget.label(period)).alias("Budget"),
+            col(period.period_label()),
+        ])
+        .collect()?;
+    Ok(groups)
+}
+
+/// Calculates the net result (revenue minus costs) of the budget posts per month
+/// of the period, with the cumulated result.
+fn monthly_result(
+    journal: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let all_months = df!("Month" => period.month_labels())?;
+    let in_period = period.contains_date();
+    let sides = [("Debit", col("Amount")), ("Credit", -col("Amount"))].map(|(side, balance)| {
+        journal.clone().lazy().filter(in_period.clone()).select([
+            col("Date").str().slice(lit(0), lit(7)).alias("Month"),
+            col(side).alias("Account"),
+            balance.alias("Balance"),
+        ])
+    });
+    let b1 = Arc::new(budget.clone());
+    let results = concat(sides, UnionArgs::default())?
+        .filter(
+            col("Account")
+                .str()
+                .contains(lit(budget.close.include.clone()), false)
+                .and(not(excluded_accounts(b1.clone()))),
+        )
+        .filter(
+            col("Account")
+                .map(
+                    move |a| get_name_of_post(&a, &b1),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::String)),
+                )
+                .is_not_null(),
+        )
+        .group_by([col("Month")])
+        // revenue is booked on the credit side, costs on the debit side
+        .agg([(-col("Balance")).sum().alias("Ergebnis")]);
+    let mut monthly = all_months
+        .lazy()
+        .join(
+            results,
+            [col("Month")],
+            [col("Month")],
+            JoinType::Left.into(),
+        )
+        .with_column(
+            col("Ergebnis")
+                .fill_null(zero())
+                .round(2, RoundMode::HalfToEven),
+        )
+        .sort(["Month"], SortMultipleOptions::default())
+        .collect()?;
+    // cumulated on the unscaled decimal values, i.e. exact
+    let results = monthly.column("Ergebnis")?.decimal()?;
+    let cumulated: Vec<i128> = results
+        .physical()
+        .into_no_null_iter()
+        .scan(0, |total, result| {
+            *total += result;
+            Some(*total)
+        })
+        .collect();
+    let cumulated = Int128Chunked::from_vec("Kumuliert".into(), cumulated)
+        .into_decimal(results.precision(), results.scale())?
+        .into_series();
+    monthly.with_column(cumulated)?;
+    Ok(monthly)
+}
+
+/// Takes the figures of the `period` of the revenue posts (factor `-1`),
+/// omitting posts without revenue.
+fn revenue_by_post(
+    aggregated: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let ytd = period.period_label();
+    let revenue = aggregated
+        .clone()
+        .lazy()
+        .join(
+            post_group_mapping(budget)?.lazy(),
+            [col("Group")],
+            [col("Group")],
+            JoinType::Left.into(),
+        )
+        .filter(col("Factor").eq(lit(-1)).and(col(&ytd).gt(zero())))
+        .select([col("Group").alias("Post"), col(&ytd)])
+        .collect()?;
+    Ok(revenue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close::budget::read_budget_config;
+    use crate::money::as_money;
+    use crate::test_fixtures::{aggregated_df_01_202505, arranged_df_01_202505, journal_df_01};
+    use crate::test_utils::assert_dataframe;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    fn read_budget_from_samples() -> Budget {
+        read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget")
+    }
+
+    #[rstest]
+    fn test_group_totals(arranged_df_01_202505: DataFrame) {
+        let expected = df!(
+            "Post Group" => ["Ertrag"],
+            "Budget" => [390.02],
+            "1.1.-31.5.25" => [1000.0],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid group totals");
+        let budget = read_budget_from_samples();
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual =
+            group_totals(&arranged_df_01_202505, &budget, &period).expect("can get group totals");
+        assert_dataframe(&actual, &expected);
+    }
+
+    #[rstest]
+    fn test_monthly_result(journal_df_01: DataFrame) {
+        let expected = df!(
+            "Month" => ["2025-01", "2025-02", "2025-03", "2025-04", "2025-05"],
+            "Ergebnis" => [1000.0, 0.0, 0.0, 0.0, 0.0],
+            "Kumuliert" => [1000.0, 1000.0, 1000.0, 1000.0, 1000.0],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid monthly result");
+        let budget = read_budget_from_samples();
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual =
+            monthly_result(&journal_df_01, &budget, &period).expect("can get monthly result");
+        assert_dataframe(&actual, &expected);
+    }
+
+    #[rstest]
+    fn test_revenue_by_post(aggregated_df_01_202505: DataFrame) {
+        let expected = df!(
+            "Post" => ["Ertrag Restauration"],
+            "1.1.-31.5.25" => [1200.0],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid revenue");
+        let budget = read_budget_from_samples();
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual = revenue_by_post(&aggregated_df_01_202505, &budget, &period)
+            .expect("can get revenue by post");
+        assert_dataframe(&actual, &expected);
+    }
+}
@@ -1,34 +1,107 @@
 use chrono::NaiveDate;
 use polars::prelude::*;
 use quick_xml::escape::unescape;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::reader::Reader;
-use serde::Deserialize;
 use std::collections::HashMap;
 use std::error::Error;
-use std::fs;
 use std::fs::File;
 use std::io::BufReader;
+use std::ops::Range;
 use std::path::Path;
+use strum_macros::{Display, EnumString};
 
 use crate::close::as_dataframe;
-use crate::export::get_last_of_month_nd;
+use crate::close::balance_sheet::balance_sheet;
+use crate::close::budget::{Budget, Post};
+use crate::close::charts::{ChartData, chart_data};
+use crate::close::cost_centres::cost_centre_statement;
+use crate::close::cross_check::{balance_differences, check_balances};
+use crate::close::drill_down::drill_down;
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::close::integrity::check_integrity;
+use crate::money::{MONEY, zero};
 
-/// read account information from xml file with ending .xls
-pub fn do_closing_xml(
+/// The content of the closing workbook
+pub struct Closing {
+    /// The figures per budget post, arranged by post group
+    pub summary: DataFrame,
+    /// The balances of the accounts excluded from the closing
+    pub excluded: DataFrame,
+    /// The opening balance, movements and balance of the balance sheet accounts
+    pub balance_sheet: DataFrame,
+    /// The journal lines contributing to the posts, with a header and a subtotal per post
+    pub drill_down: DataFrame,
+    /// The journal lines failing the integrity checks, with the remark in `Check`
+    pub checks: DataFrame,
+    /// The balances of the accounts file of the previous year differing from its journal, if compared with
+    pub previous_differences: Option<DataFrame>,
+    /// The profit and loss statement per cost centre
+    pub cost_centres: DataFrame,
+    /// The data of the charts
+    pub charts: ChartData,
+}
+
+/// read account information from xml file with ending .xls for the `period`,
+/// optionally comparing it with the journal of the previous year
+pub(super) fn do_closing_xml(
     input_path: &Path,
-    budget: Budget,
-    month: &str,
-) -> Result<DataFrame, Box<dyn Error>> {
-    let journal = read_xml(input_path)?;
-    let dummy_accounts = as_dataframe(budget.get_first_account_per_post(), month)?;
+    previous_input_path: Option<&Path>,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<Closing, Box<dyn Error>> {
+    let (journal, accounts) = read_checked_xml(input_path)?;
+    let dummy_accounts = as_dataframe(budget.get_first_account_per_post(), &period.first_date())?;
     let extended = journal.vstack(&dummy_accounts)?;
-    let aggregated = aggregate_balances(&extended, budget, month)?;
-    Ok(aggregated)
+    let (previous_journal, previous_differences) = previous_input_path
+        .map(read_previous_xml)
+        .transpose()?
+        .unzip();
+    let aggregated = aggregate_balances(&extended, previous_journal.as_ref(), budget, period)?;
+    let summary = arrange_by_post_groups(&aggregated, budget, period)?;
+    Ok(Closing {
+        charts: chart_data(&journal, &aggregated, &summary, budget, period)?,
+        summary,
+        excluded: get_excluded_balances(&journal, &accounts, budget, period)?,
+        balance_sheet: balance_sheet(&journal, &accounts, budget, period)?,
+        drill_down: drill_down(&journal, &accounts, budget, period)?,
+        checks: check_integrity(&journal, &accounts, budget, period)?,
+        previous_differences,
+        cost_centres: cost_centre_statement(&journal, budget, period)?,
+    })
+}
+
+/// Reads the journal and the accounts, failing if the balances computed by Banana
+/// (sheets Accounts and Totals) differ from the ones derived from the journal.
+pub(super) fn read_checked_xml(
+    input_path: &Path,
+) -> Result<(DataFrame, DataFrame), Box<dyn Error>> {
+    let journal = read_xml(input_path)?;
+    let accounts = read_accounts(input_path)?;
+    check_balances(&journal, &accounts, &read_totals(input_path)?)
+        .map_err(|e| format!("{}: {e}", input_path.display()))?;
+    Ok((journal, accounts))
+}
+
+/// Reads the journal of the previous year with the balances computed by Banana differing from the ones
+/// derived from it, which are only reported, as the previous year is just compared with.
+fn read_previous_xml(input_path: &Path) -> Result<(DataFrame, DataFrame), Box<dyn Error>> {
+    let journal = read_xml(input_path)?;
+    let differences = balance_differences(
+        &journal,
+        &read_accounts(input_path)?,
+        &read_totals(input_path)?,
+    )?;
+    Ok((journal, differences))
 }
 
 /// reads the Excel XML format, extracting columns from sheet Journal:
-/// Date, Description, Debit, Credit, Amount.
+/// Date, Description, Debit, Credit, Amount (as [`MONEY`]) and the cost centres Cc1, Cc2, Cc3.
+/// The columns are located via the header row with the XML names of the columns,
+/// falling back to the default positions if there is none (without cost centres).
+/// Lines with a missing or invalid date or an unparseable amount are kept with a null `Date` or `Amount`
+/// to be reported by the integrity checks, rows without date, accounts and amount are skipped.
+#[allow(clippy::too_many_lines, clippy::collapsible_match)]
 fn read_xml(input_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
     let file = BufReader::new(File::open(input_path)?);
     let mut reader = Reader::from_reader(file);
@@ -41,6 +114,9 @@ fn read_xml(input_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
     let mut cell_value = String::new();
     let mut index: Option<u32> = None;
     let mut row: HashMap<u32, String> = HashMap::new();
+    let mut header_row = false;
+    let mut column_indexes = JournalColumn::ALL.map(|c| c as u32);
+    let mut cost_centre_indexes: Option<[u32; 3]> = None;
     let mut df = new_empty_frame()?;
 
     loop {
@@ -57,6 +133,11 @@ fn read_xml(input_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
                         }
                     }
                 }
+                b"Row" => {
+                    if in_sheet {
+                        header_row = is_header_row(e)?;
+                    }
+                }
                 b"Cell" => {
                     for attr in e.attributes().flatten() {
                         if attr.key.as_ref() == b"ss:Index" {
@@ -86,35 +167,50 @@ fn read_xml(input_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
                 }
                 b"Row" => {
                     if in_sheet {
-                        let date_index = JournalColumn::Date as u32;
-                        let description_index = JournalColumn::Description as u32;
-                        let debit_index = JournalColumn::Debit as u32;
-                        let credit_index = JournalColumn::Credit as u32;
-                        let amount_index = JournalColumn::Amount as u32;
-                        let mut df_row = new_row(
-                            &row.remove(&date_index).unwrap_or("1/1/2100".into()),
-                            row.remove(&description_index)
-                                .unwrap_or("description missing".into()),
-                            &row.remove(&debit_index).unwrap_or("debit missing".into()),
-                            &row.remove(&credit_index).unwrap_or("credit missing".into()),
-                            &row.remove(&amount_index).unwrap_or("0.0".into()),
-                        )?;
-                        df_row = df_row.fill_null(FillNullStrategy::Zero)?;
-                        let filtered = df_row
-                            .clone()
-                            .lazy()
-                            .filter(
-                                col("Date").is_not_null().and(
-                                    col("Date")
-                                        .str()
-                                        .contains(lit(r"^\d{4}-\d{2}-\d{2}$"), false),
-                                ),
-                            )
-                            .select([col("Date")])
-                            .collect()
-                            .unwrap();
-                        if filtered.shape().0 > 0 {
-                            df = df.vstack(&df_row)?;
+                        if header_row {
+                            if row.get(&1).map(String::as_str) == Some(Sheet::Journal.name()) {
+                                if let Some(indexes) = find_columns(&row, &JournalColumn::XML_NAMES)
+                                    && let Ok(indexes) = indexes.try_into()
+                                {
+                                    column_indexes = indexes;
+                                }
+                                if let Some(indexes) = find_columns(&row, &COST_CENTRE_XML_NAMES)
+                                    && let Ok(indexes) = indexes.try_into()
+                                {
+                                    cost_centre_indexes = Some(indexes);
+                                }
+                            }
+                        } else {
+                            let [
+                                date_index,
+                                description_index,
+                                debit_index,
+                                credit_index,
+                                amount_index,
+                            ] = column_indexes;
+                            let cost_centres = cost_centre_indexes
+                                .map(|indexes| indexes.map(|i| cost_centre(row.remove(&i))))
+                                .unwrap_or_default();
+                            let date = row.remove(&date_index);
+                            let debit = row.remove(&debit_index);
+                            let credit = row.remove(&credit_index);
+                            let amount = row.remove(&amount_index);
+                            if date.is_some()
+                                || debit.is_some()
+                                || credit.is_some()
+                                || amount.is_some()
+                            {
+                                let df_row = new_row(
+                                    date.as_deref(),
+                                    row.remove(&description_index)
+                                        .unwrap_or("description missing".into()),
+                                    &debit.unwrap_or(DEBIT_MISSING.into()),
+                                    &credit.unwrap_or(CREDIT_MISSING.into()),
+                                    amount.as_deref().unwrap_or("0.0"),
+                                    cost_centres,
+                                )?;
+                                df = df.vstack(&df_row)?;
+                            }
                         }
                         row.clear();
                     }
@@ -135,57 +231,212 @@ fn read_xml(input_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
     Ok(df)
 }
 
-/// calculates the first day to be ignored, e.g.
-/// returns 2025-08-01 for month 202507
-fn cut_off_date(last_valid_month: &str) -> String {
-    let year: i32 = last_valid_month[0..4].parse().unwrap();
-    let month: u32 = last_valid_month[4..6].parse().unwrap();
-    let (next_year, next_month) = if month == 12 {
-        (year + 1, 1)
-    } else {
-        (year, month + 1)
-    };
-    NaiveDate::from_ymd_opt(next_year, next_month, 1).map_or("2000-01-01".to_string(), |d| {
-        d.format("%Y-%m-%d").to_string()
-    })
+/// Reads the accounts of the sheet Accounts: `Account`, `Description`, base class (`BClass`),
+/// and the `Opening` balance and `Balance` computed by Banana.
+/// Rows without account (e.g. groups and totals) are skipped.
+fn read_accounts(input_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
+    let accounts = read_sheet(
+        input_path,
+        Sheet::Accounts,
+        &["Account", "Description", "BClass", "Opening", "Balance"],
+    )?
+    .lazy()
+    .filter(col("Account").neq(lit("")))
+    .with_columns([
+        col("Opening").cast(MONEY).fill_null(zero()),
+        col("Balance").cast(MONEY).fill_null(zero()),
+    ])
+    .collect()?;
+    Ok(accounts)
 }
 
+/// Reads the totals of the sheet Totals: `Group`, `Description` and the `Balance` computed by Banana.
+fn read_totals(input_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
+    let totals = read_sheet(
+        input_path,
+        Sheet::Totals,
+        &["Group", "Description", "Balance"],
+    )?
+    .lazy()
+    .filter(col("Group").neq(lit("")))
+    .with_column(col("Balance").cast(MONEY).fill_null(zero()))
+    .collect()?;
+    Ok(totals)
+}
+
+/// Reads the requested `columns` of the given sheet as strings.
+/// The columns are located via the header row with the XML names of the columns,
+/// i.e. the first header row with the name of the sheet in the first cell containing all of them.
+/// Only the data rows following the header are read.
+/// Returns an empty frame if the sheet is not present.
+fn read_sheet(
+    input_path: &Path,
+    sheet: Sheet,
+    columns: &[&str],
+) -> Result<DataFrame, Box<dyn Error>> {
+    let file = BufReader::new(File::open(input_path)?);
+    let mut reader = Reader::from_reader(file);
+
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut in_sheet = false;
+    let mut in_cell = false;
+    let mut cell_value = String::new();
+    let mut index: u32 = 0;
+    let mut row: HashMap<u32, String> = HashMap::new();
+    let mut sheet_found = false;
+    let mut header_row = false;
+    let mut column_indexes: Option<Vec<u32>> = None;
+    let mut values: Vec<Vec<String>> = vec![Vec::new(); columns.len()];
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                b"Worksheet" => {
+                    for attr in e.attributes().flatten() {
+                        if attr.key.as_ref() == b"ss:Name" {
+                            let raw_value = str::from_utf8(attr.value.as_ref())?;
+                            in_sheet = unescape(raw_value)? == sheet.name();
+                            sheet_found |= in_sheet;
+                        }
+                    }
+                }
+                b"Row" if in_sheet => {
+                    index = 0;
+                    row.clear();
+                    header_row = is_header_row(e)?;
+                }
+                b"Cell" if in_sheet => {
+                    index += 1;
+                    for attr in e.attributes().flatten() {
+                        if attr.key.as_ref() == b"ss:Index" {
+                            let raw_value = str::from_utf8(attr.value.as_ref())?;
+                            index = unescape(raw_value)?.parse::<u32>()?;
+                        }
+                    }
+                    cell_value.clear();
+                    in_cell = true;
+                }
+                _ => {}
+            },
+            Ok(Event::End(ref e)) => match e.name().as_ref() {
+                b"Worksheet" => {
+                    in_sheet = false;
+                    in_cell = false;
+                }
+                b"Cell" if in_cell => {
+                    row.insert(index, cell_value.clone());
+                    in_cell = false;
+                }
+                b"Row" if in_sheet => {
+                    if header_row {
+                        if column_indexes.is_none()
+                            && row.get(&1).map(String::as_str) == Some(sheet.name())
+                        {
+                            column_indexes = find_columns(&row, columns);
+                        }
+                    } else if let Some(indexes) = &column_indexes {
+                        for (i, column_values) in indexes.iter().zip(values.iter_mut()) {
+                            column_values.push(row.remove(i).unwrap_or_default());
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_sheet && in_cell => {
+                cell_value = e.decode()?.into_owned();
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(Box::from(e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+    if sheet_found && column_indexes.is_none() {
+        return Err(format!(
+            "Columns {} not found in sheet {}",
+            columns.join(", "),
+            sheet.name()
+        )
+        .into());
+    }
+    let df_columns = columns
+        .iter()
+        .zip(values)
+        .map(|(c, v)| Column::new((*c).into(), v))
+        .collect();
+    Ok(DataFrame::new(df_columns)?)
+}
+
+/// Tells if the row is part of the header of a sheet (the rows preceding the data).
+fn is_header_row(row: &BytesStart) -> Result<bool, Box<dyn Error>> {
+    for attr in row.attributes().flatten() {
+        if attr.key.as_ref() == b"ss:StyleID" {
+            return Ok(str::from_utf8(attr.value.as_ref())? == HEADER_STYLE);
+        }
+    }
+    Ok(false)
+}
+
+/// Locates the `columns` in a header row, returns `None` if any of them is missing.
+fn find_columns(row: &HashMap<u32, String>, columns: &[&str]) -> Option<Vec<u32>> {
+    columns
+        .iter()
+        .map(|c| row.iter().find(|(_, v)| v.as_str() == *c).map(|(i, _)| *i))
+        .collect()
+}
+
+/// The code of the cost centre of a journal line, empty if there is none.
+/// A leading minus (booking the cost centre with the opposite sign) is kept.
+fn cost_centre(value: Option<String>) -> String {
+    value.map(|v| v.trim().to_string()).unwrap_or_default()
+}
+
+/// A journal line with the `date` (`yyyy-mm-dd`, followed by the time) and the `amount`,
+/// the `Date` being null if it is missing or invalid and the `Amount` if it cannot be parsed.
 fn new_row(
-    date: &str,
+    date: Option<&str>,
     description: String,
     debit: &str,
     credit: &str,
     amount: &str,
+    cost_centres: [String; 3],
 ) -> Result<DataFrame, Box<dyn Error>> {
-    let date_trunc = &date[..10.min(date.len())];
-    let amount_numeric: f64 = amount.parse().unwrap_or(0.0);
+    let date = date
+        .map(|d| d.get(..10).unwrap_or(d))
+        .filter(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok());
+    let amount = amount.parse::<f64>().is_ok().then_some(amount);
     new_row_with_vecs(
-        vec![date_trunc.to_string()],
+        vec![date.map(String::from)],
         vec![description],
         vec![debit.into()],
         vec![credit.into()],
-        vec![amount_numeric],
+        vec![amount.map(String::from)],
+        cost_centres.map(|c| vec![c]),
     )
 }
 
 fn new_empty_frame() -> Result<DataFrame, Box<dyn Error>> {
     new_row_with_vecs(
-        Vec::<String>::new(),
-        Vec::<String>::new(),
-        Vec::<String>::new(),
-        Vec::<String>::new(),
-        Vec::<f64>::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Default::default(),
     )
 }
 
 fn new_row_with_vecs(
-    date: Vec<String>,
+    date: Vec<Option<String>>,
     description: Vec<String>,
     debit: Vec<String>,
     credit: Vec<String>,
-    amount: Vec<f64>,
+    amount: Vec<Option<String>>,
+    cost_centres: [Vec<String>; 3],
 ) -> Result<DataFrame, Box<dyn Error>> {
-    let df = DataFrame::new(vec![
+    let mut df = DataFrame::new(vec![
         Column::new(
             JournalColumn::Date.name().into(),
             Series::new(JournalColumn::Date.name().into(), date),
@@ -204,25 +455,45 @@ fn new_row_with_vecs(
         ),
         Column::new(
             JournalColumn::Amount.name().into(),
-            Series::new(JournalColumn::Amount.name().into(), amount),
+            // parsed from its decimal representation, i.e. exact
+            Series::new(JournalColumn::Amount.name().into(), amount).cast(&MONEY)?,
         ),
     ])?;
+    for (name, values) in COST_CENTRES.into_iter().zip(cost_centres) {
+        df.with_column(Column::new(name.into(), values))?;
+    }
     Ok(df)
 }
 
-/// gets the balances and aggregates them on budget level
-fn aggregate_balances(
+/// gets the balances and aggregates them on budget level,
+/// adding the figures of the previous year if its journal is provided
+pub(super) fn aggregate_balances(
     journal: &DataFrame,
-    budget: Budget,
-    month: &str,
+    previous_journal: Option<&DataFrame>,
+    budget: &Budget,
+    period: &FiscalPeriod,
 ) -> Result<DataFrame, Box<dyn Error>> {
-    let balances = get_balances_from(journal, month)?;
-    let aggregated = enrich_and_aggregate(&balances, budget, month)?;
-    Ok(aggregated)
+    let balances = get_balances_from(journal, &budget.close.include, period.contains_date())?;
+    let ytd_balances = get_balances_from(
+        journal,
+        &budget.close.include,
+        period.year_to_date().contains_date(),
+    )?;
+    let aggregated = enrich_and_aggregate(&balances, &ytd_balances, budget, period)?;
+    let aggregated = match previous_journal {
+        Some(previous) => compare_with_previous_year(&aggregated, previous, budget, period)?,
+        None => aggregated,
+    };
+    Ok(aggregated.drop("Net YTD")?)
 }
 
-/// Transforms the journal into balances for the relevant accounts up to and including the month specified.
-fn get_balances_from(journal: &DataFrame, month: &str) -> Result<DataFrame, Box<dyn Error>> {
+/// Transforms the journal into balances for the accounts matching the regular expression `include`,
+/// considering the journal lines matching `in_period` (e.g. up to and including the closing month).
+pub(super) fn get_balances_from(
+    journal: &DataFrame,
+    include: &str,
+    in_period: Expr,
+) -> Result<DataFrame, Box<dyn Error>> {
     let debits = journal
         .clone()
         .lazy()
@@ -235,15 +506,14 @@ fn get_balances_from(journal: &DataFrame, month: &str) -> Result<DataFrame, Box<
         .collect()?;
     let debits_and_credits = debits.vstack(&credits)?;
 
-    let cut_off_date = cut_off_date(month);
     let balances = debits_and_credits
         .clone()
         .lazy()
         .filter(
             col("Account")
                 .str()
-                .contains(lit(r"^[3456789]\d{3,4}$"), false)
-                .and(col("Date").lt(lit(cut_off_date.clone()))),
+                .contains(lit(include), false)
+                .and(in_period),
         )
         .group_by(["Account"])
         .agg(&[col("Amount").sum().alias("Balance")])
@@ -251,22 +521,78 @@ fn get_balances_from(journal: &DataFrame, month: &str) -> Result<DataFrame, Box<
     Ok(balances)
 }
 
-/// enriches the balances with budget
+/// enriches the balances of the period with budget, the remaining budget (`Verbleibend`) being
+/// the annual budget minus the figures of the period, the deviation (`Abweichung YTD`) the one
+/// from the budget planned for the months of the period.
+/// The forecast is based on the year-to-date balances (`ytd_balances`), kept as helper column `Net YTD`.
 fn enrich_and_aggregate(
     balances: &DataFrame,
-    budget: Budget,
-    month: &str,
+    ytd_balances: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
 ) -> Result<DataFrame, Box<dyn Error>> {
-    let year = month.chars().take(4).collect::<String>();
-    let budget_alias = format!("Budget {year}");
-    let lom = get_last_of_month_nd(month)?;
-    let lom_formatted = lom.format("%-d.%-m.%y");
-    let month_alias = format!("1.1.-{lom_formatted}");
-    let budget = Arc::new(budget);
+    let budget_alias = budget.label(period);
+    let month_alias = period.period_label();
+    let year_to_date = period.year_to_date();
+    let months = year_to_date.months();
+    let ytd_net = aggregate_by_post(ytd_balances, budget, &year_to_date)?
+        .lazy()
+        .select([col("Group"), col("Net").alias("Net YTD")]);
+    let aggregated = aggregate_by_post(balances, budget, period)?
+        .lazy()
+        .join(
+            ytd_net,
+            [col("Group")],
+            [col("Group")],
+            JoinType::Left.into(),
+        )
+        .select([
+            col("Group"),
+            col("Budget").alias(budget_alias),
+            col("Net").alias(month_alias),
+            ((col("Budget") - col("Net")) * col("Factor"))
+                .round(2, RoundMode::HalfToEven)
+                .alias("Verbleibend"),
+            col("Budget YTD").round(2, RoundMode::HalfToEven),
+            ((col("Budget YTD") - col("Net")) * col("Factor"))
+                .round(2, RoundMode::HalfToEven)
+                .alias("Abweichung YTD"),
+            // linear run-rate of the year to date extrapolated to the whole year
+            linear_forecast(months).alias("Prognose"),
+            ((col("Budget") - linear_forecast(months)) * col("Factor"))
+                .round(2, RoundMode::HalfToEven)
+                .alias("Abweichung Prognose"),
+            col("Net YTD"),
+        ])
+        .collect()?;
+    Ok(aggregated)
+}
+
+/// The year-to-date figures (`Net YTD`) of `months` months extrapolated to twelve months, rounded to cents.
+/// The extrapolation itself is an estimate and calculated in floating point.
+fn linear_forecast(months: u32) -> Expr {
+    (col("Net YTD").cast(DataType::Float64) * lit(12.0) / lit(f64::from(months)))
+        .cast(MONEY)
+        .round(2, RoundMode::HalfToEven)
+}
+
+/// aggregates the balances on the level of the budget posts, sorted by post,
+/// resulting in the columns `Group`, `Sort`, `Budget`, `Budget YTD`, `Factor` and `Net`,
+/// with `Budget YTD` being the part of the budget planned for the months of the period.
+fn aggregate_by_post(
+    balances: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let year = period.budget_year();
+    let ytd_year = year.clone();
+    let months = period.fiscal_months();
+    let budget = Arc::new(budget.clone());
     let b1 = budget.clone();
     let b2 = budget.clone();
     let b3 = budget.clone();
-    let b4 = budget;
+    let b4 = budget.clone();
+    let b5 = budget.clone();
     let enriched = balances
         .clone()
         .lazy()
@@ -298,42 +624,353 @@ fn enrich_and_aggregate(
             col("Account")
                 .map(
                     move |a| get_budget_of_post(&a, &b4, &year.clone()),
-                    |_, field| Ok(Field::new(field.name().clone(), DataType::Int64)),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::Float64)),
                 )
+                .cast(MONEY)
                 .alias("Budget"),
         )
-        .filter(
-            // Exceptional accounts that need not be included
-            col("Account").neq(lit("8900")),
+        .with_column(
+            col("Account")
+                .map(
+                    move |a| get_budget_ytd_of_post(&a, &b5, &ytd_year, months.clone()),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::Float64)),
+                )
+                .cast(MONEY)
+                .alias("Budget YTD"),
         )
+        .filter(not(excluded_accounts(budget.clone())))
         .collect()?;
 
     validate_all_accounts_are_in_budget(&enriched)?;
 
-    //    let x = budget
-    //        .join(
-    //            enriched.clone(),
-    //            [col("Group")],
-    //            [col("Account")],
-    //            JoinType::Left.into(),
-    //        )
-    //        .collect()?;
-
     let aggregated = enriched
         .clone()
         .lazy()
-        .with_column((col("Balance").fill_null(lit(0.0)) * col("Factor")).alias("Net"))
-        .group_by(["Group", "Sort", "Budget", "Factor"])
+        .with_column(
+            (col("Balance").fill_null(zero()) * col("Factor"))
+                .cast(MONEY)
+                .alias("Net"),
+        )
+        .group_by(["Group", "Sort", "Budget", "Budget YTD", "Factor"])
         .agg(&[col("Net").sum()])
         .sort(["Sort"], SortMultipleOptions::default())
+        .collect()?;
+    Ok(aggregated)
+}
+
+/// Lists the balances of the accounts excluded from the closing
+/// (`[close] exclude` of the budget configuration) within the period,
+/// with the `Description` of the accounts.
+fn get_excluded_balances(
+    journal: &DataFrame,
+    accounts: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let balances = get_balances_from(journal, &budget.close.include, period.contains_date())?;
+    let excluded = balances
+        .lazy()
+        .filter(excluded_accounts(Arc::new(budget.clone())))
+        .join(
+            account_descriptions(accounts),
+            [col("Account")],
+            [col("Account")],
+            JoinType::Left.into(),
+        )
         .select([
-            col("Group"),
-            col("Budget").alias(budget_alias),
-            col("Net").alias(month_alias),
-            ((col("Budget") - col("Net")) * col("Factor")).alias("Verbleibend"),
+            col("Account"),
+            col("Description").fill_null(lit("")),
+            col("Balance"),
         ])
+        .sort(["Account"], SortMultipleOptions::default())
         .collect()?;
-    Ok(aggregated)
+    Ok(excluded)
+}
+
+/// The `Description` per `Account`
+pub(super) fn account_descriptions(accounts: &DataFrame) -> LazyFrame {
+    accounts
+        .clone()
+        .lazy()
+        .select([col("Account"), col("Description")])
+        .unique(Some(cols(["Account"])), UniqueKeepStrategy::First)
+}
+
+/// Boolean expression telling if the `Account` is excluded from the closing
+pub(super) fn excluded_accounts(budget: Arc<Budget>) -> Expr {
+    col("Account").map(
+        move |a| {
+            Ok(a.str()?
+                .into_iter()
+                .map(|a| a.map(|a| budget.is_excluded(a)))
+                .collect::<BooleanChunked>()
+                .into_column())
+        },
+        |_, field| Ok(Field::new(field.name().clone(), DataType::Boolean)),
+    )
+}
+
+/// Extends the `aggregated` closing figures with the figures of the same period of the previous year
+/// (labelled with the period of the previous year) and its total figures (derived from the `previous_journal`)
+/// and the relative deviation of the current figures from the ones of the same period of the previous year.
+/// The accounts of the previous year are mapped to the posts via the same `budget`.
+/// The forecast (`Prognose`) follows the seasonality of the previous year,
+/// i.e. the year-to-date figures (`Net YTD`) are scaled by the ratio of the total to the year-to-date figures
+/// of the previous year up to the same month. Posts without year-to-date figures in the previous year
+/// keep the linear forecast.
+fn compare_with_previous_year(
+    aggregated: &DataFrame,
+    previous_journal: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let previous = period.previous();
+    let previous_label = previous.period_label();
+    let previous_period = net_by_group(previous_journal, budget, &previous, &previous_label)?;
+    let previous_total = net_by_group(
+        previous_journal,
+        budget,
+        &previous.full_year(),
+        "Vorjahr total",
+    )?;
+    let previous_to_date = net_by_group(
+        previous_journal,
+        budget,
+        &previous.year_to_date(),
+        "Vorjahr bis Monat",
+    )?;
+    let current_label = period.period_label();
+    let budget_alias = budget.label(period);
+    let factors = post_group_mapping(budget)?
+        .lazy()
+        .select([col("Group"), col("Factor")]);
+    let with_sort = aggregated
+        .clone()
+        .lazy()
+        .with_row_index("Row", None)
+        .join(
+            factors,
+            [col("Group")],
+            [col("Group")],
+            JoinType::Left.into(),
+        )
+        .join(
+            previous_period.lazy(),
+            [col("Group")],
+            [col("Group")],
+            JoinType::Left.into(),
+        )
+        .join(
+            previous_total.lazy(),
+            [col("Group")],
+            [col("Group")],
+            JoinType::Left.into(),
+        )
+        .join(
+            previous_to_date.lazy(),
+            [col("Group")],
+            [col("Group")],
+            JoinType::Left.into(),
+        )
+        .with_column(col(&previous_label).fill_null(zero()))
+        .with_column(col("Vorjahr total").fill_null(zero()))
+        .with_column(col("Vorjahr bis Monat").fill_null(zero()))
+        .with_column(
+            when(col("Vorjahr bis Monat").neq(zero()))
+                .then(
+                    (col("Net YTD").cast(DataType::Float64)
+                        * col("Vorjahr total").cast(DataType::Float64)
+                        / col("Vorjahr bis Monat").cast(DataType::Float64))
+                    .cast(MONEY)
+                    .round(2, RoundMode::HalfToEven),
+                )
+                .otherwise(col("Prognose"))
+                .alias("Prognose"),
+        )
+        .with_column(
+            ((col(budget_alias.as_str()) - col("Prognose")) * col("Factor"))
+                .cast(MONEY)
+                .round(2, RoundMode::HalfToEven)
+                .alias("Abweichung Prognose"),
+        )
+        .with_column(delta_previous_year(&current_label, &previous_label))
+        .sort(["Row"], SortMultipleOptions::default())
+        .drop(cols(["Row", "Factor", "Vorjahr bis Monat"]))
+        .collect()?;
+    Ok(with_sort)
+}
+
+/// Arranges the posts of the `aggregated` closing figures by post group,
+/// adding a header and a subtotal row per group and the net result as last row.
+/// The kind of each row is provided in the additional column `Row Type`.
+#[allow(clippy::too_many_lines)]
+fn arrange_by_post_groups(
+    aggregated: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let mapping = post_group_mapping(budget)?;
+
+    let values: Vec<String> = aggregated
+        .get_column_names_str()
+        .into_iter()
+        .filter(|c| *c != "Group")
+        .map(ToString::to_string)
+        .collect();
+    let columns_of = |value_exprs: Vec<Expr>, row_type: RowType, order: i32| {
+        let mut columns = vec![col("Group")];
+        columns.extend(value_exprs);
+        columns.extend([
+            lit(row_type.to_string()).alias("Row Type"),
+            col("Post Group"),
+            col("Group Sort"),
+            lit(order).alias("Order"),
+            col("Row"),
+        ]);
+        columns
+    };
+
+    let posts = aggregated.clone().lazy().with_row_index("Row", None).join(
+        mapping.lazy(),
+        [col("Group")],
+        [col("Group")],
+        JoinType::Left.into(),
+    );
+    let headers = posts
+        .clone()
+        .group_by([col("Post Group"), col("Group Sort")])
+        .agg([col("Row").min()])
+        .with_column(col("Post Group").alias("Group"))
+        .select(columns_of(
+            values
+                .iter()
+                .map(|v| {
+                    let dtype = aggregated.column(v).map(|c| c.dtype().clone());
+                    lit(NULL).cast(dtype.unwrap_or(MONEY)).alias(v.as_str())
+                })
+                .collect(),
+            RowType::Header,
+            0,
+        ));
+    let subtotals = posts
+        .clone()
+        .group_by([col("Post Group"), col("Group Sort")])
+        .agg(
+            values
+                .iter()
+                .map(|v| col(v.as_str()).sum().round(2, RoundMode::HalfToEven))
+                .chain([col("Row").max()])
+                .collect::<Vec<_>>(),
+        )
+        .with_column(concat_str([lit("Total "), col("Post Group")], "", false).alias("Group"))
+        .select(columns_of(
+            values.iter().map(|v| col(v.as_str())).collect(),
+            RowType::Subtotal,
+            2,
+        ));
+    let post_rows = posts.clone().select(columns_of(
+        values.iter().map(|v| col(v.as_str())).collect(),
+        RowType::Post,
+        1,
+    ));
+    let result = posts
+        .select(
+            values
+                .iter()
+                .map(|v| {
+                    // the deviations from the budget are already signed in favour of the result
+                    if DEVIATIONS.contains(&v.as_str()) {
+                        col(v.as_str()).sum()
+                    } else {
+                        (-(col(v.as_str()) * col("Factor"))).sum()
+                    }
+                    .round(2, RoundMode::HalfToEven)
+                })
+                .chain([
+                    lit(RESULT_LABEL).alias("Group"),
+                    lit("").alias("Post Group"),
+                    lit(i64::MAX).alias("Group Sort"),
+                    lit(0).cast(IDX_DTYPE).alias("Row"),
+                ])
+                .collect::<Vec<_>>(),
+        )
+        .select(columns_of(
+            values.iter().map(|v| col(v.as_str())).collect(),
+            RowType::Result,
+            3,
+        ));
+
+    let mut arranged = concat(
+        [headers, post_rows, subtotals, result],
+        UnionArgs::default(),
+    )?
+    .sort(
+        ["Group Sort", "Order", "Row"],
+        SortMultipleOptions::default(),
+    );
+    if values.iter().any(|v| v == DELTA_PREVIOUS_YEAR) {
+        arranged = arranged.with_column(delta_previous_year(
+            &period.period_label(),
+            &period.previous().period_label(),
+        ));
+    }
+    let mut columns = vec![col("Group")];
+    columns.extend(values.iter().map(|v| col(v.as_str())));
+    columns.push(col("Row Type"));
+    Ok(arranged.select(columns).collect()?)
+}
+
+/// Maps the name of each post (`Group`) to the name of its post group (`Post Group`),
+/// the position of the post group (`Group Sort`) and the `Factor` of the post.
+pub(super) fn post_group_mapping(budget: &Budget) -> PolarsResult<DataFrame> {
+    let mut post_names = Vec::new();
+    let mut post_groups = Vec::new();
+    let mut group_sorts = Vec::new();
+    let mut factors = Vec::new();
+    for (group_sort, (group_name, post_keys)) in (0_i64..).zip(budget.get_ordered_post_groups()) {
+        for post in post_keys.iter().filter_map(|k| budget.posts.get(k)) {
+            post_names.push(post.name.clone());
+            post_groups.push(group_name.clone());
+            group_sorts.push(group_sort);
+            factors.push(post.factor);
+        }
+    }
+    df!(
+        "Group" => post_names,
+        "Post Group" => post_groups,
+        "Group Sort" => group_sorts,
+        "Factor" => factors,
+    )
+}
+
+/// The deviation of the figures in column `current`
+/// from the ones of the same period of the previous year in column `previous` in percent.
+fn delta_previous_year(current: &str, previous: &str) -> Expr {
+    let previous = col(previous).cast(DataType::Float64);
+    when(previous.clone().eq(lit(0.0)))
+        .then(lit(NULL).cast(DataType::Float64))
+        .otherwise(
+            ((col(current).cast(DataType::Float64) - previous.clone()) / previous.abs()
+                * lit(100.0))
+            .round(1, RoundMode::HalfToEven),
+        )
+        .alias(DELTA_PREVIOUS_YEAR)
+}
+
+/// Returns the net amount per post (column `Group`) from the `journal`
+/// within the `period`, aliased as `alias`.
+fn net_by_group(
+    journal: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+    alias: &str,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let balances = get_balances_from(journal, &budget.close.include, period.contains_date())?;
+    let net = aggregate_by_post(&balances, budget, period)?
+        .lazy()
+        .select([col("Group"), col("Net").alias(alias)])
+        .collect()?;
+    Ok(net)
 }
 
 // validates the processed data does not contain any accounts that are not in the budget
@@ -354,7 +991,7 @@ fn validate_all_accounts_are_in_budget(enriched: &DataFrame) -> Result<(), Box<d
 }
 
 /// Finds the descriptions of the budget post for given account
-fn get_name_of_post(col: &Column, budget: &Budget) -> PolarsResult<Column> {
+pub(super) fn get_name_of_post(col: &Column, budget: &Budget) -> PolarsResult<Column> {
     let accounts = col.str()?;
     Ok(accounts
         .into_iter()
@@ -376,8 +1013,28 @@ fn get_budget_of_post(col: &Column, budget: &Budget, year: &str) -> PolarsResult
         .into_column())
 }
 
+/// Finds the budget amount of the budget post for given account and year
+/// planned for the `months` of the fiscal year according to the phasing of the post
+fn get_budget_ytd_of_post(
+    col: &Column,
+    budget: &Budget,
+    year: &str,
+    months: Range<usize>,
+) -> PolarsResult<Column> {
+    let accounts = col.str()?;
+    Ok(accounts
+        .into_iter()
+        .map(|a| {
+            a.map(|a| budget.get_budget_ytd_amount_by_account(a, year, months.clone()))
+                .transpose()
+        })
+        .collect::<Result<Float64Chunked, String>>()
+        .map_err(|e| polars_err!(ComputeError: "{e}"))?
+        .into_column())
+}
+
 /// Finds the sort for the post for given account
-fn get_sort_of_post(col: &Column, budget: &Budget) -> PolarsResult<Column> {
+pub(super) fn get_sort_of_post(col: &Column, budget: &Budget) -> PolarsResult<Column> {
     get_int_from_post(col, budget, |p| p.sort)
 }
 
@@ -401,11 +1058,33 @@ where
         .into_column())
 }
 
+/// The columns with deviations from the budget, signed in favour of the result
+const DEVIATIONS: [&str; 3] = ["Verbleibend", "Abweichung YTD", "Abweichung Prognose"];
+
+/// The column with the deviation from the same period of the previous year in percent
+pub(super) const DELTA_PREVIOUS_YEAR: &str = "Delta Vorjahr %";
+
+/// The label of the row with the net result of all posts
+const RESULT_LABEL: &str = "Rein-Gew./-Verlust";
+
+/// The kind of row in the closing report
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, Display)]
+pub enum RowType {
+    /// Header of a post group
+    Header,
+    /// Budget post
+    Post,
+    /// Subtotal of a post group
+    Subtotal,
+    /// Net result of all posts
+    Result,
+}
+
 /// The worksheets in the workbook
 #[derive(Debug, Clone, Copy)]
 enum Sheet {
-    _Accounts = 0,
-    _Totals = 1,
+    Accounts = 0,
+    Totals = 1,
     Journal = 2,
     _FileInfo = 3,
 }
@@ -413,15 +1092,31 @@ enum Sheet {
 impl Sheet {
     fn name(self) -> &'static str {
         match self {
-            Sheet::_Accounts => "Accounts",
-            Sheet::_Totals => "Totals",
+            Sheet::Accounts => "Accounts",
+            Sheet::Totals => "Totals",
             Sheet::Journal => "Journal",
             Sheet::_FileInfo => "FileInfo",
         }
     }
 }
 
+/// Placeholder for the debit account of a journal line without one
+pub(super) const DEBIT_MISSING: &str = "debit missing";
+
+/// Placeholder for the credit account of a journal line without one
+pub(super) const CREDIT_MISSING: &str = "credit missing";
+
+/// The columns with the cost centres of the journal lines
+pub(super) const COST_CENTRES: [&str; 3] = ["Cc1", "Cc2", "Cc3"];
+
+/// The XML names of the cost centre columns in the worksheet Journal, in the order of `COST_CENTRES`
+const COST_CENTRE_XML_NAMES: [&str; 3] = ["Cc1", "Cc2", "Cc3"];
+
+/// The style of the header rows of the worksheets
+const HEADER_STYLE: &str = "sHeader";
+
 /// The columns in the worksheet Journal, index is one-based
+/// (default position if the worksheet has no header row with the XML names)
 #[derive(Debug, Clone, Copy)]
 enum JournalColumn {
     Date = 8,
@@ -432,6 +1127,23 @@ enum JournalColumn {
 }
 
 impl JournalColumn {
+    const ALL: [JournalColumn; 5] = [
+        JournalColumn::Date,
+        JournalColumn::Description,
+        JournalColumn::Debit,
+        JournalColumn::Credit,
+        JournalColumn::Amount,
+    ];
+
+    /// The XML names of the columns, in the order of `ALL`
+    const XML_NAMES: [&'static str; 5] = [
+        "Date",
+        "Description",
+        "AccountDebit",
+        "AccountCredit",
+        "Amount",
+    ];
+
     fn name(self) -> &'static str {
         match self {
             JournalColumn::Date => "Date",
@@ -443,86 +1155,16 @@ impl JournalColumn {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
-pub struct Budget {
-    #[serde(rename = "name")]
-    _name: String,
-    #[serde(rename = "post_groups")]
-    _post_groups: HashMap<String, PostGroup>,
-    posts: HashMap<String, Post>,
-    years: HashMap<String, Year>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-struct PostGroup {
-    #[serde(rename = "name")]
-    _name: String,
-    #[serde(rename = "posts")]
-    _posts: Vec<String>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-struct Post {
-    pub name: String,
-    account_codes: Vec<String>,
-    sort: i64,
-    factor: i64,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-struct Year {
-    amounts: HashMap<String, f64>,
-}
-
-impl Budget {
-    /// Get the post by account code
-    fn get_post_by_account(&self, account: &str) -> Option<&Post> {
-        self.posts
-            .values()
-            .find(|p| p.account_codes.contains(&account.to_string()))
-    }
-
-    fn get_post_key_by_account(&self, account: &str) -> Option<String> {
-        self.posts
-            .iter()
-            .find(|(_, p)| p.account_codes.contains(&account.to_string()))
-            .map(|(k, _)| k.clone())
-    }
-
-    /// get the amount for the given budget account and year
-    fn get_budget_amount_by_account(&self, account: &str, year: &str) -> f64 {
-        let post_key_option = self.get_post_key_by_account(account);
-        if let Some(post_key) = post_key_option {
-            let x = self.years.get(year);
-            x.and_then(|y| y.amounts.get(&post_key))
-                .copied()
-                .unwrap_or(-0.0)
-        } else {
-            -0.0
-        }
-    }
-
-    /// returns the first account per post
-    fn get_first_account_per_post(&self) -> Vec<String> {
-        self.posts
-            .values()
-            .filter_map(|post| post.account_codes.first())
-            .cloned()
-            .collect()
-    }
-}
-
-pub fn read_budget_config(budget_config_file: &Path) -> Result<Budget, Box<dyn Error>> {
-    let toml_str = fs::read_to_string(budget_config_file)?;
-    let budget: Budget = toml::from_str(&toml_str)?;
-    Ok(budget)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::close::budget::read_budget_config;
+    use crate::money::as_money;
     use crate::{
-        test_fixtures::{aggregated_df_01_202505, aggregated_df_01_202507, journal_df_01},
+        test_fixtures::{
+            accounts_df_01, aggregated_df_01_202505, aggregated_df_01_202505_with_previous,
+            aggregated_df_01_202507, arranged_df_01_202505, journal_df_01, journal_df_01_previous,
+        },
         test_utils::assert_dataframe,
     };
     use rstest::rstest;
@@ -538,39 +1180,135 @@ mod tests {
         budget
     }
 
-    #[rstest]
-    fn can_read_from_sample_config_file() {
-        let budget = read_budget_from_samples();
-        let Some(post) = budget.get_post_by_account("30100") else {
-            panic!("Account 30100 not found in budget");
-        };
-        assert_eq!("Ertrag Restauration", post.name);
-    }
-
     #[rstest]
     fn can_run_closing() {
         let budget = read_budget_from_samples();
 
         let data = "samples/konten_202412_20250128132200.xls".to_string();
         let data_file = &PathBuf::from(data);
-        let _result = do_closing_xml(data_file, budget, "202410")
+        let period = budget.fiscal_period("202410").expect("valid period");
+        let _result = do_closing_xml(data_file, None, &budget, &period)
             .expect("Unable to process sample data file.");
     }
 
     #[rstest]
-    fn can_get_dummy_posts() {
+    #[case(
+        Some("2025-03-14T00:00:00"),
+        "12.50",
+        Some("2025-03-14"),
+        Some(125_000)
+    )]
+    #[case(None, "12.50", None, Some(125_000))]
+    #[case(Some("14.3.2025"), "12.50", None, Some(125_000))]
+    #[case(Some("2025-02-30"), "12.50", None, Some(125_000))]
+    #[case(Some("2025-03-14"), "12,50", Some("2025-03-14"), None)]
+    fn can_keep_invalid_date_and_amount(
+        #[case] date: Option<&str>,
+        #[case] amount: &str,
+        #[case] expected_date: Option<&str>,
+        #[case] expected_amount: Option<i128>,
+    ) {
+        let row = new_row(
+            date,
+            "Miete".into(),
+            "10100",
+            "30100",
+            amount,
+            Default::default(),
+        )
+        .expect("can create journal row");
+        assert_eq!(
+            row.column("Date").unwrap().str().unwrap().get(0),
+            expected_date
+        );
+        assert_eq!(
+            row.column("Amount")
+                .unwrap()
+                .decimal()
+                .unwrap()
+                .physical()
+                .get(0),
+            expected_amount
+        );
+    }
+
+    #[rstest]
+    fn given_differing_previous_year_when_closing_we_should_report_differences() {
         let budget = read_budget_from_samples();
-        let accounts = budget.get_first_account_per_post();
-        assert_ne!(accounts.len(), 0);
+        let data_file = &PathBuf::from("samples/konten_202412_20250128132200.xls");
+        let previous_file = std::env::temp_dir().join(format!(
+            "konten_202312_{}_differing_previous_year.xls",
+            std::process::id()
+        ));
+        let sample = std::fs::read_to_string(data_file).expect("can read sample data file");
+        std::fs::write(
+            &previous_file,
+            sample.replace(r#"ss:Type="Number">10.00<"#, r#"ss:Type="Number">12.00<"#),
+        )
+        .expect("can write differing data file");
+        assert!(read_checked_xml(&previous_file).is_err());
+
+        let period = budget.fiscal_period("202410").expect("valid period");
+        let closing = do_closing_xml(data_file, Some(&previous_file), &budget, &period);
+        std::fs::remove_file(&previous_file).expect("can remove differing data file");
+        let closing = closing.expect("differences of the previous year do not fail the closing");
+        let differences = closing
+            .previous_differences
+            .expect("previous year compared with");
+        let accounts = differences
+            .column("Account")
+            .and_then(|c| c.str().cloned())
+            .expect("accounts of differences");
+        assert_eq!(
+            accounts.into_no_null_iter().collect::<Vec<_>>(),
+            vec!["10100", "30200", "1", "4"]
+        );
     }
 
     #[rstest]
-    #[case("202411", "2024-12-01")]
-    #[case("202412", "2025-01-01")]
-    #[case("202507", "2025-08-01")]
-    fn test_cut_off_date(#[case] month: &str, #[case] expected: &str) {
-        let actual = cut_off_date(month);
-        assert_eq!(actual, expected);
+    fn can_read_accounts_from_sample() {
+        let data_file = &PathBuf::from("samples/konten_202412_20250128132200.xls");
+        let accounts = read_accounts(data_file).expect("can read accounts of sample data file");
+        assert_eq!(
+            accounts.get_column_names_str(),
+            vec!["Account", "Description", "BClass", "Opening", "Balance"]
+        );
+        let sumup = accounts
+            .lazy()
+            .filter(col("Account").eq(lit("10920")))
+            .collect()
+            .expect("can filter accounts");
+        assert_eq!(
+            sumup.column("Description").unwrap().str().unwrap().get(0),
+            Some("Durchlaufkonto Sumup")
+        );
+    }
+
+    #[rstest]
+    fn can_read_journal_and_totals_from_sample() {
+        let data_file = &PathBuf::from("samples/konten_202412_20250128132200.xls");
+        let journal = read_xml(data_file).expect("can read journal of sample data file");
+        let first_row = journal.get_row(0).expect("journal has rows").0;
+        assert_eq!(
+            first_row,
+            vec![
+                AnyValue::String("2024-01-31"),
+                AnyValue::String("a"),
+                AnyValue::String("10000"),
+                AnyValue::String("30200"),
+                AnyValue::Decimal(50_000, 38, 4),
+                AnyValue::String(""),
+                AnyValue::String(""),
+                AnyValue::String(""),
+            ]
+        );
+        let totals = read_totals(data_file).expect("can read totals of sample data file");
+        assert_eq!(
+            totals.get_column_names_str(),
+            vec!["Group", "Description", "Balance"]
+        );
+        let accounts = read_accounts(data_file).expect("can read accounts of sample data file");
+        check_balances(&journal, &accounts, &totals).expect("sample balances do not differ");
     }
 
     #[rstest]
@@ -589,9 +1327,120 @@ mod tests {
         test_aggregate_balances_by("202507", &journal_df_01, &aggregated_df_01_202507);
     }
 
+    #[rstest]
+    fn test_aggregate_balances_with_previous_year(
+        journal_df_01: DataFrame,
+        journal_df_01_previous: DataFrame,
+        aggregated_df_01_202505_with_previous: DataFrame,
+    ) {
+        let budget = read_budget_from_samples();
+        let actual = aggregate_balances(
+            &journal_df_01,
+            Some(&journal_df_01_previous),
+            &budget,
+            &budget.fiscal_period("202505").expect("valid period"),
+        )
+        .expect("can aggregate balances with previous year");
+        assert_dataframe(&actual, &aggregated_df_01_202505_with_previous);
+    }
+
+    #[rstest]
+    fn test_arrange_by_post_groups(
+        aggregated_df_01_202505: DataFrame,
+        arranged_df_01_202505: DataFrame,
+    ) {
+        let budget = read_budget_from_samples();
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual = arrange_by_post_groups(&aggregated_df_01_202505, &budget, &period)
+            .expect("can arrange by post groups");
+        assert_dataframe(&actual, &arranged_df_01_202505);
+    }
+
+    #[rstest]
+    fn test_get_excluded_balances(journal_df_01: DataFrame, accounts_df_01: DataFrame) {
+        let budget = read_budget_from_samples();
+        let taxes = df!(
+            "Date" => &["2025-03-31", "2025-06-30"],
+            "Description" => &["Steuern Q1", "Steuern Q2"],
+            "Debit" => &["8900", "8900"],
+            "Credit" => &["10100", "10100"],
+            "Amount" => &[50.0, 70.0],
+            "Cc1" => &["", ""],
+            "Cc2" => &["", ""],
+            "Cc3" => &["", ""],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid journal dataframe");
+        let journal = journal_df_01.vstack(&taxes).expect("can extend journal");
+        let expected = df!(
+            "Account" => &["8900"],
+            "Description" => &["Direkte Steuern"],
+            "Balance" => &[50.0],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid excluded dataframe");
+
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual = get_excluded_balances(&journal, &accounts_df_01, &budget, &period)
+            .expect("can get excluded balances");
+        assert_dataframe(&actual, &expected);
+
+        let aggregated = aggregate_balances(&journal, None, &budget, &period)
+            .expect("can aggregate balances without excluded accounts");
+        assert_eq!(aggregated.height(), 2);
+    }
+
+    #[rstest]
+    fn test_aggregate_balances_of_fiscal_year_starting_in_july(journal_df_01: DataFrame) {
+        let mut budget = read_budget_from_samples();
+        budget.fiscal_year_start = 7;
+        let period = budget.fiscal_period("202507").expect("valid period");
+        let expected = df!(
+           "Group" => ["Ertrag Restauration", "Ertrag Vermietungen"],
+           "Budget 2025/26" => [30.01, 31.01],
+           "1.7.-31.7.25" => [-100.0, 100.0],
+           "Verbleibend" => [-130.01, 68.99],
+           "Budget YTD" => [2.5, 2.58],
+           "Abweichung YTD" => [-102.5, 97.42],
+           "Prognose" => [-1200.0, 1200.0],
+           "Abweichung Prognose" => [-1230.01, 1168.99],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid aggregated dataframe");
+        let actual = aggregate_balances(&journal_df_01, None, &budget, &period)
+            .expect("can aggregate balances");
+        assert_dataframe(&actual, &expected);
+    }
+
+    #[rstest]
+    fn test_aggregate_balances_of_single_month(journal_df_01: DataFrame) {
+        let budget = read_budget_from_samples();
+        let period = budget
+            .fiscal_period("202505")
+            .and_then(|p| p.starting_from("202505"))
+            .expect("valid period");
+        let expected = df!(
+           "Group" => ["Ertrag Restauration", "Spenden"],
+           "Budget 2025" => [30.01, 360.01],
+           "1.5.-31.5.25" => [200.0, -200.0],
+           "Verbleibend" => [169.99, -560.01],
+           "Budget YTD" => [2.5, 30.0],
+           "Abweichung YTD" => [197.5, -230.0],
+           "Prognose" => [2880.0, -480.0],
+           "Abweichung Prognose" => [2849.99, -840.01],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid aggregated dataframe");
+        let actual = aggregate_balances(&journal_df_01, None, &budget, &period)
+            .expect("can aggregate balances");
+        assert_dataframe(&actual, &expected);
+    }
+
     fn test_aggregate_balances_by(month: &str, journal: &DataFrame, expected: &DataFrame) {
         let budget = read_budget_from_samples();
-        let actual = aggregate_balances(journal, budget, month).expect("can aggregate balances");
+        let period = budget.fiscal_period(month).expect("valid period");
+        let actual =
+            aggregate_balances(journal, None, &budget, &period).expect("can aggregate balances");
         assert_dataframe(&actual, expected);
     }
 }
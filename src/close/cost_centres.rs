@@ -0,0 +1,321 @@
+use crate::close::budget::Budget;
+use crate::close::close_xml::{COST_CENTRES, RowType, excluded_accounts};
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::money::{MONEY, zero};
+use polars::prelude::*;
+use std::error::Error;
+use std::sync::Arc;
+
+/// The name of the worksheet with the profit and loss statement per cost centre
+pub const COST_CENTRE_STATEMENT: &str = "kostenstellen";
+
+/// Lists the profit and loss statement per cost centre for the period, ordered by the name
+/// of the cost centre: a header row with the name (from `cost_centres` of the budget, the code otherwise),
+/// the posts with the budget of the cost centre, the figures of the period and the remaining budget,
+/// and the net result (revenue minus costs).
+/// A journal line is attributed to each cost centre in its columns `Cc1`, `Cc2` and `Cc3`,
+/// with the opposite sign if the code has a leading minus (e.g. `-KULTUR`),
+/// the figures are signed like the ones in the closing report.
+#[allow(clippy::too_many_lines)]
+pub fn cost_centre_statement(
+    journal: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let budget_alias = budget.label(period);
+    let period_alias = period.period_label();
+
+    let lines = journal
+        .clone()
+        .lazy()
+        .with_row_index("Row", None)
+        .filter(period.contains_date());
+    let tagged = COST_CENTRES.map(|cost_centre| {
+        let negated = col(cost_centre).str().starts_with(lit("-"));
+        lines.clone().filter(col(cost_centre).neq(lit(""))).select([
+            col("Row"),
+            col(cost_centre)
+                .str()
+                .strip_prefix(lit("-"))
+                .alias("Cost Centre"),
+            col("Debit"),
+            col("Credit"),
+            when(negated)
+                .then(-col("Amount"))
+                .otherwise(col("Amount"))
+                .alias("Amount"),
+        ])
+    });
+    let tagged = concat(tagged, UnionArgs::default())?.unique(
+        Some(cols(["Row", "Cost Centre"])),
+        UniqueKeepStrategy::First,
+    );
+    let sides = [("Debit", col("Amount")), ("Credit", -col("Amount"))].map(|(side, balance)| {
+        tagged.clone().select([
+            col("Cost Centre"),
+            col(side).alias("Account"),
+            balance.alias("Balance"),
+        ])
+    });
+    let b1 = Arc::new(budget.clone());
+    let b2 = b1.clone();
+    let actuals = concat(sides, UnionArgs::default())?
+        .filter(
+            col("Account")
+                .str()
+                .contains(lit(budget.close.include.clone()), false)
+                .and(not(excluded_accounts(b1))),
+        )
+        .with_column(
+            col("Account")
+                .map(
+                    move |a| get_post_key(&a, &b2),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::String)),
+                )
+                .alias("Post Key"),
+        )
+        .filter(col("Post Key").is_not_null())
+        .group_by([col("Cost Centre"), col("Post Key")])
+        .agg([col("Balance").sum()]);
+
+    let statement = actuals
+        .join(
+            cost_centre_budgets(budget, &period.budget_year())?.lazy(),
+            [col("Cost Centre"), col("Post Key")],
+            [col("Cost Centre"), col("Post Key")],
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .join(
+            posts(budget)?.lazy(),
+            [col("Post Key")],
+            [col("Post Key")],
+            JoinType::Inner.into(),
+        )
+        .join(
+            cost_centre_names(budget)?.lazy(),
+            [col("Cost Centre")],
+            [col("Cost Centre")],
+            JoinType::Left.into(),
+        )
+        .with_columns([
+            col("Name").fill_null(col("Cost Centre")),
+            col("Budget").cast(MONEY).fill_null(zero()),
+        ])
+        .with_column(
+            (col("Balance").fill_null(zero()) * col("Factor"))
+                .cast(MONEY)
+                .alias("Net"),
+        )
+        .with_column(
+            ((col("Budget") - col("Net")) * col("Factor"))
+                .cast(MONEY)
+                .alias("Verbleibend"),
+        );
+
+    let columns_of = |group: Expr, values: [Expr; 3], row_type: RowType, order: i32| {
+        [
+            col("Name"),
+            col("Cost Centre"),
+            group.alias("Group"),
+            values[0]
+                .clone()
+                .round(2, RoundMode::HalfToEven)
+                .alias(&budget_alias),
+            values[1]
+                .clone()
+                .round(2, RoundMode::HalfToEven)
+                .alias(&period_alias),
+            values[2]
+                .clone()
+                .round(2, RoundMode::HalfToEven)
+                .alias("Verbleibend"),
+            lit(row_type.to_string()).alias("Row Type"),
+            lit(order).alias("Order"),
+            col("Sort"),
+        ]
+    };
+    let per_cost_centre = [col("Name"), col("Cost Centre")];
+    let headers = statement
+        .clone()
+        .group_by(per_cost_centre.clone())
+        .agg([col("Sort").min()])
+        .select(columns_of(
+            col("Name"),
+            [0, 1, 2].map(|_| lit(NULL).cast(MONEY)),
+            RowType::Header,
+            0,
+        ));
+    let post_rows = statement.clone().select(columns_of(
+        col("Post"),
+        [col("Budget"), col("Net"), col("Verbleibend")],
+        RowType::Post,
+        1,
+    ));
+    let results = statement
+        .group_by(per_cost_centre)
+        .agg([
+            // revenue minus costs, the remaining budget is already signed in favour of the result
+            (-(col("Budget") * col("Factor"))).sum().cast(MONEY),
+            (-(col("Net") * col("Factor"))).sum().cast(MONEY),
+            col("Verbleibend").sum(),
+            col("Sort").max(),
+        ])
+        .select(columns_of(
+            concat_str([lit("Ergebnis "), col("Name")], "", false),
+            [col("Budget"), col("Net"), col("Verbleibend")],
+            RowType::Result,
+            2,
+        ));
+
+    let statement = concat([headers, post_rows, results], UnionArgs::default())?
+        .sort(
+            ["Name", "Cost Centre", "Order", "Sort"],
+            SortMultipleOptions::default(),
+        )
+        .select([
+            col("Group"),
+            col(&budget_alias),
+            col(&period_alias),
+            col("Verbleibend"),
+            col("Row Type"),
+        ])
+        .collect()?;
+    Ok(statement)
+}
+
+/// The budget amounts of the cost centres for the `year` (`Cost Centre`, `Post Key`, `Budget`)
+fn cost_centre_budgets(budget: &Budget, year: &str) -> PolarsResult<DataFrame> {
+    let (mut cost_centres, mut post_keys, mut amounts) = (Vec::new(), Vec::new(), Vec::new());
+    for (code, cost_centre) in &budget.cost_centres {
+        for (post_key, amount) in cost_centre.years.get(year).iter().flat_map(|y| &y.amounts) {
+            cost_centres.push(code.clone());
+            post_keys.push(post_key.clone());
+            amounts.push(*amount);
+        }
+    }
+    df!(
+        "Cost Centre" => cost_centres,
+        "Post Key" => post_keys,
+        "Budget" => amounts,
+    )
+}
+
+/// The posts of the budget (`Post Key`, `Post`, `Sort`, `Factor`)
+fn posts(budget: &Budget) -> PolarsResult<DataFrame> {
+    let (mut keys, mut names, mut sorts, mut factors) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for (key, post) in &budget.posts {
+        keys.push(key.clone());
+        names.push(post.name.clone());
+        sorts.push(post.sort);
+        factors.push(post.factor);
+    }
+    df!(
+        "Post Key" => keys,
+        "Post" => names,
+        "Sort" => sorts,
+        "Factor" => factors,
+    )
+}
+
+/// The names of the cost centres configured in the budget (`Cost Centre`, `Name`)
+fn cost_centre_names(budget: &Budget) -> PolarsResult<DataFrame> {
+    let (codes, names): (Vec<_>, Vec<_>) = budget
+        .cost_centres
+        .iter()
+        .map(|(code, cost_centre)| (code.clone(), cost_centre.name.clone()))
+        .unzip();
+    df!(
+        "Cost Centre" => codes,
+        "Name" => names,
+    )
+}
+
+/// Finds the key of the budget post for given account
+fn get_post_key(col: &Column, budget: &Budget) -> PolarsResult<Column> {
+    Ok(col
+        .str()?
+        .into_iter()
+        .map(|a| a.and_then(|a| budget.get_post_key_by_account(a)))
+        .collect::<StringChunked>()
+        .into_column())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close::budget::read_budget_config;
+    use crate::money::as_money;
+    use crate::test_fixtures::journal_df_01;
+    use crate::test_utils::assert_dataframe;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_cost_centre_statement(journal_df_01: DataFrame) {
+        let budget =
+            read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget");
+        let expected = df!(
+            "Group" => [
+                "KULTUR", "Ertrag Restauration", "Ertrag Vermietungen", "Ergebnis KULTUR",
+                "Sommerfest", "Ertrag Restauration", "Kulturelle Einnahmen", "Spenden", "Ergebnis Sommerfest",
+            ],
+            "Budget 2025" => [
+                None, Some(0.0), Some(0.0), Some(0.0),
+                None, Some(0.0), Some(800.0), Some(0.0), Some(800.0),
+            ],
+            "1.1.-31.7.25" => [
+                None, Some(-100.0), Some(100.0), Some(0.0),
+                None, Some(200.0), Some(0.0), Some(-200.0), Some(0.0),
+            ],
+            "Verbleibend" => [
+                None, Some(-100.0), Some(100.0), Some(0.0),
+                None, Some(200.0), Some(-800.0), Some(-200.0), Some(-800.0),
+            ],
+            "Row Type" => [
+                "Header", "Post", "Post", "Result",
+                "Header", "Post", "Post", "Post", "Result",
+            ],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid cost centre statement");
+        let period = budget.fiscal_period("202507").expect("valid period");
+        let actual =
+            cost_centre_statement(&journal_df_01, &budget, &period).expect("can list cost centres");
+        assert_dataframe(&actual, &expected);
+    }
+
+    #[rstest]
+    fn given_negated_cost_centre_when_listing_cost_centres_we_should_book_the_opposite_sign() {
+        let budget =
+            read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget");
+        let journal = df!(
+            "Date" => &["2025-01-10", "2025-02-10"],
+            "Description" => &["Konzert", "Konzert Rückerstattung"],
+            "Debit" => &["10100", "10100"],
+            "Credit" => &["30100", "30100"],
+            "Amount" => &[1000.0, 300.0],
+            "Cc1" => &["KULTUR", ""],
+            "Cc2" => &["", "-KULTUR"],
+            "Cc3" => &["", ""],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid journal dataframe");
+        let expected = df!(
+            "Group" => [
+                "KULTUR", "Ertrag Restauration", "Ergebnis KULTUR",
+                "Sommerfest", "Kulturelle Einnahmen", "Ergebnis Sommerfest",
+            ],
+            "Budget 2025" => [None, Some(0.0), Some(0.0), None, Some(800.0), Some(800.0)],
+            "1.1.-28.2.25" => [None, Some(700.0), Some(700.0), None, Some(0.0), Some(0.0)],
+            "Verbleibend" => [None, Some(700.0), Some(700.0), None, Some(-800.0), Some(-800.0)],
+            "Row Type" => ["Header", "Post", "Result", "Header", "Post", "Result"],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid cost centre statement");
+        let period = budget.fiscal_period("202502").expect("valid period");
+        let actual =
+            cost_centre_statement(&journal, &budget, &period).expect("can list cost centres");
+        assert_dataframe(&actual, &expected);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::close::close_xml::{CREDIT_MISSING, DEBIT_MISSING, get_balances_from};
+use crate::money::zero;
+use polars::prelude::*;
+use std::error::Error;
+
+/// Regular expression matching all accounts booked in the journal
+const ALL_ACCOUNTS: &str = ".+";
+
+/// Regular expression matching the cost centres in the sheet Accounts (`.`, `,` and `;` for `Cc1` to `Cc3`)
+const COST_CENTRE_ACCOUNTS: &str = r"^[.,;]";
+
+/// Regular expression matching the totals per base class (`BClass`) in the sheet Totals
+const BASE_CLASS_TOTALS: &str = r"^[1-9]$";
+
+/// Fails if the balances computed by Banana differ from the ones derived from the journal,
+/// listing the differing accounts and totals: the accounts file is incomplete or not read correctly.
+pub fn check_balances(
+    journal: &DataFrame,
+    accounts: &DataFrame,
+    totals: &DataFrame,
+) -> Result<(), Box<dyn Error>> {
+    let differences = balance_differences(journal, accounts, totals)?;
+    if differences.height() > 0 {
+        println!("{differences:?}");
+        Err(format!(
+            "Balances of {} accounts or totals differ from the ones computed by Banana, \
+            the accounts file seems to be incomplete or not to be read correctly",
+            differences.height()
+        )
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Compares the balances computed by Banana with the ones derived from the `journal`:
+/// the `Balance` of the `accounts` with their `Opening` balance and movements,
+/// and the `Balance` of the `totals` per base class with the sum of the balances of its accounts.
+/// Accounts booked in the journal but missing in `accounts` are listed with a Banana balance of `0`,
+/// missing debit or credit accounts of journal lines are ignored.
+/// The cost centres listed in `accounts` are not compared, as the journal never books them as accounts.
+/// Returns the differing rows (`Sheet`, `Account`, `Description`, `Banana`, `Journal`, `Difference`).
+pub fn balance_differences(
+    journal: &DataFrame,
+    accounts: &DataFrame,
+    totals: &DataFrame,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let movements = get_balances_from(journal, ALL_ACCOUNTS, lit(true))?
+        .lazy()
+        .filter(
+            col("Account")
+                .neq(lit(DEBIT_MISSING))
+                .and(col("Account").neq(lit(CREDIT_MISSING))),
+        )
+        .select([col("Account"), col("Balance").alias("Movements")]);
+    let computed = accounts
+        .clone()
+        .lazy()
+        .filter(not(col("Account")
+            .str()
+            .contains(lit(COST_CENTRE_ACCOUNTS), false)))
+        .join(
+            movements,
+            [col("Account")],
+            [col("Account")],
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .with_columns([
+            col("Balance").fill_null(zero()).alias("Banana"),
+            (col("Opening").fill_null(zero()) + col("Movements").fill_null(zero()))
+                .round(2, RoundMode::HalfToEven)
+                .alias("Journal"),
+        ]);
+    let class_totals = computed
+        .clone()
+        .group_by([col("BClass")])
+        .agg([col("Journal").sum()]);
+
+    let by_account = computed.select([
+        lit("Accounts").alias("Sheet"),
+        col("Account"),
+        col("Description").fill_null(lit("")),
+        col("Banana"),
+        col("Journal"),
+    ]);
+    let by_class = totals
+        .clone()
+        .lazy()
+        .filter(col("Group").str().contains(lit(BASE_CLASS_TOTALS), false))
+        .join(
+            class_totals,
+            [col("Group")],
+            [col("BClass")],
+            JoinType::Left.into(),
+        )
+        .select([
+            lit("Totals").alias("Sheet"),
+            col("Group").alias("Account"),
+            col("Description"),
+            col("Balance").fill_null(zero()).alias("Banana"),
+            col("Journal")
+                .fill_null(zero())
+                .round(2, RoundMode::HalfToEven),
+        ]);
+    let differences = concat([by_account, by_class], UnionArgs::default())?
+        .with_column(
+            (col("Banana") - col("Journal"))
+                .round(2, RoundMode::HalfToEven)
+                .alias("Difference"),
+        )
+        .filter(col("Difference").neq(zero()))
+        .sort(["Sheet", "Account"], SortMultipleOptions::default())
+        .collect()?;
+    Ok(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::as_money;
+    use crate::test_fixtures::{accounts_df_01, journal_df_01, totals_df_01};
+    use crate::test_utils::assert_dataframe;
+    use rstest::rstest;
+
+    #[rstest]
+    fn balances_of_complete_export_do_not_differ(
+        journal_df_01: DataFrame,
+        accounts_df_01: DataFrame,
+        totals_df_01: DataFrame,
+    ) {
+        let differences = balance_differences(&journal_df_01, &accounts_df_01, &totals_df_01)
+            .expect("can compare balances");
+        assert_eq!(differences.height(), 0);
+        check_balances(&journal_df_01, &accounts_df_01, &totals_df_01)
+            .expect("balances do not differ");
+    }
+
+    #[rstest]
+    fn can_find_differing_balances(accounts_df_01: DataFrame, totals_df_01: DataFrame) {
+        let incomplete = df!(
+            "Date" => &["2025-01-01", "2025-05-16", "2025-07-20"],
+            "Description" => &["Posting 1", "Posting 2", "Posting 3"],
+            "Debit" => &["10100", "36000", "30100"],
+            "Credit" => &["30100", "30700", "39000"],
+            "Amount" => &[1000.0, 200.0, 50.0],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid journal dataframe");
+        let expected = df!(
+            "Sheet" => &["Accounts", "Accounts", "Accounts", "Totals"],
+            "Account" => &["30100", "31000", "39000", "4"],
+            "Description" => &["Ertrag Café", "Ertrag Vermietungen", "", "Totalsumme Ertrag"],
+            "Banana" => &[-900.0, -100.0, 0.0, -1000.0],
+            "Journal" => &[-950.0, 0.0, -50.0, -950.0],
+            "Difference" => &[50.0, -100.0, 50.0, -50.0],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid differences dataframe");
+
+        let actual = balance_differences(&incomplete, &accounts_df_01, &totals_df_01)
+            .expect("can compare balances");
+        assert_dataframe(&actual, &expected);
+        assert!(check_balances(&incomplete, &accounts_df_01, &totals_df_01).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::close::budget::Budget;
+use crate::close::close_xml::{
+    RowType, account_descriptions, excluded_accounts, get_name_of_post, get_sort_of_post,
+    post_group_mapping,
+};
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::money::MONEY;
+use polars::prelude::*;
+use std::error::Error;
+use std::sync::Arc;
+
+/// Lists the journal lines of the period that contribute to the budget posts,
+/// ordered like the posts in the closing report. The lines of each post are preceded by a header
+/// row with the name of the post (in `Description`) and followed by its subtotal.
+/// A line booked on two posts (debit and credit) is listed for both of them.
+/// The `Amount` is signed like the year-to-date figures in the closing report,
+/// the `Account` booked on the post is listed with its description (from `accounts`).
+#[allow(clippy::too_many_lines)]
+pub fn drill_down(
+    journal: &DataFrame,
+    accounts: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let in_period = period.contains_date();
+    let sides = [("Debit", col("Amount")), ("Credit", -col("Amount"))].map(|(side, balance)| {
+        journal.clone().lazy().filter(in_period.clone()).select([
+            col("Date"),
+            col("Description"),
+            col("Debit"),
+            col("Credit"),
+            col(side).alias("Account"),
+            balance.alias("Balance"),
+        ])
+    });
+    let mapping = post_group_mapping(budget)?.lazy().select([
+        col("Group").alias("Post"),
+        col("Post Group"),
+        col("Group Sort"),
+        col("Factor"),
+    ]);
+
+    let b1 = Arc::new(budget.clone());
+    let b2 = b1.clone();
+    let lines = concat(sides, UnionArgs::default())?
+        .with_row_index("Row", None)
+        .filter(
+            col("Account")
+                .str()
+                .contains(lit(budget.close.include.clone()), false)
+                .and(not(excluded_accounts(b1.clone()))),
+        )
+        .with_column(
+            col("Account")
+                .map(
+                    move |a| get_name_of_post(&a, &b1),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::String)),
+                )
+                .alias("Post"),
+        )
+        .with_column(
+            col("Account")
+                .map(
+                    move |a| get_sort_of_post(&a, &b2),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::Int64)),
+                )
+                .cast(DataType::Int64)
+                .alias("Post Sort"),
+        )
+        .filter(col("Post").is_not_null())
+        .join(
+            mapping,
+            [col("Post")],
+            [col("Post")],
+            JoinType::Inner.into(),
+        )
+        .join(
+            account_descriptions(accounts).select([
+                col("Account"),
+                col("Description").alias("Account Description"),
+            ]),
+            [col("Account")],
+            [col("Account")],
+            JoinType::Left.into(),
+        )
+        .with_columns([
+            (col("Balance") * col("Factor"))
+                .cast(MONEY)
+                .round(2, RoundMode::HalfToEven)
+                .alias("Amount"),
+            col("Account Description").fill_null(lit("")),
+        ]);
+
+    let columns_of = |row_type: RowType, order: i32| {
+        [
+            col("Date"),
+            col("Description"),
+            col("Debit"),
+            col("Credit"),
+            col("Amount"),
+            col("Account"),
+            col("Account Description"),
+            col("Post"),
+            col("Post Group"),
+            lit(row_type.to_string()).alias("Row Type"),
+            col("Group Sort"),
+            col("Post Sort"),
+            lit(order).alias("Order"),
+            col("Row"),
+        ]
+    };
+    let per_post = [
+        col("Post"),
+        col("Post Group"),
+        col("Group Sort"),
+        col("Post Sort"),
+    ];
+    let headers = lines
+        .clone()
+        .group_by(per_post.clone())
+        .agg([col("Row").min()])
+        .with_columns([
+            lit(NULL).cast(DataType::String).alias("Date"),
+            col("Post").alias("Description"),
+            lit(NULL).cast(DataType::String).alias("Debit"),
+            lit(NULL).cast(DataType::String).alias("Credit"),
+            lit(NULL).cast(MONEY).alias("Amount"),
+            lit(NULL).cast(DataType::String).alias("Account"),
+            lit(NULL)
+                .cast(DataType::String)
+                .alias("Account Description"),
+        ])
+        .select(columns_of(RowType::Header, 0));
+    let subtotals = lines
+        .clone()
+        .group_by(per_post)
+        .agg([
+            col("Amount").sum().round(2, RoundMode::HalfToEven),
+            col("Row").max(),
+        ])
+        .with_columns([
+            lit(NULL).cast(DataType::String).alias("Date"),
+            concat_str([lit("Total "), col("Post")], "", false).alias("Description"),
+            lit(NULL).cast(DataType::String).alias("Debit"),
+            lit(NULL).cast(DataType::String).alias("Credit"),
+            lit(NULL).cast(DataType::String).alias("Account"),
+            lit(NULL)
+                .cast(DataType::String)
+                .alias("Account Description"),
+        ])
+        .select(columns_of(RowType::Subtotal, 2));
+    let post_lines = lines.select(columns_of(RowType::Post, 1));
+
+    let drill_down = concat([headers, post_lines, subtotals], UnionArgs::default())?
+        .sort(
+            ["Group Sort", "Post Sort", "Post", "Order", "Date", "Row"],
+            SortMultipleOptions::default(),
+        )
+        .select([
+            col("Date"),
+            col("Description"),
+            col("Debit"),
+            col("Credit"),
+            col("Amount"),
+            col("Account"),
+            col("Account Description"),
+            col("Post"),
+            col("Post Group"),
+            col("Row Type"),
+        ])
+        .collect()?;
+    Ok(drill_down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close::budget::read_budget_config;
+    use crate::test_fixtures::{accounts_df_01, drill_down_df_01_202505, journal_df_01};
+    use crate::test_utils::assert_dataframe;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_drill_down(
+        journal_df_01: DataFrame,
+        accounts_df_01: DataFrame,
+        drill_down_df_01_202505: DataFrame,
+    ) {
+        let budget =
+            read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget");
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual =
+            drill_down(&journal_df_01, &accounts_df_01, &budget, &period).expect("can drill down");
+        assert_dataframe(&actual, &drill_down_df_01_202505);
+    }
+}
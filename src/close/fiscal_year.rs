@@ -0,0 +1,365 @@
+use chrono::{Datelike, Months, NaiveDate};
+use polars::prelude::*;
+use std::error::Error;
+use std::ops::Range;
+
+/// The reporting period within a fiscal year, by default from its start
+/// up to and including the closing month
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct FiscalPeriod {
+    /// The first day of the fiscal year
+    start: NaiveDate,
+    /// The first day of the first month reported
+    from: NaiveDate,
+    /// The first day of the closing month
+    month: NaiveDate,
+}
+
+impl FiscalPeriod {
+    /// The period of the fiscal year starting with month `start_month` (`1` to `12`)
+    /// up to and including the closing `month` (`yyyymm`, e.g. 202505).
+    pub(super) fn new(month: &str, start_month: u32) -> Result<Self, Box<dyn Error>> {
+        if !(1..=12).contains(&start_month) {
+            return Err(format!(
+                "Invalid fiscal year start {start_month}, expected a month from 1 to 12"
+            )
+            .into());
+        }
+        let month = parse_month(month)?;
+        let start_year = if month.month() < start_month {
+            month.year() - 1
+        } else {
+            month.year()
+        };
+        let start = NaiveDate::from_ymd_opt(start_year, start_month, 1)
+            .ok_or("Invalid start of fiscal year")?;
+        Ok(FiscalPeriod {
+            start,
+            from: start,
+            month,
+        })
+    }
+
+    /// The quarter (`yyyyQn`, e.g. 2025Q2) of the fiscal year starting with month `start_month`
+    /// in year `yyyy`, i.e. quarter 1 covers the first three months of the fiscal year.
+    pub(super) fn quarter(quarter: &str, start_month: u32) -> Result<Self, Box<dyn Error>> {
+        let invalid = || format!("Invalid quarter {quarter}, expected <yyyy>Q<1-4>, e.g. 2025Q2");
+        let (year, number) = quarter.split_once('Q').ok_or_else(invalid)?;
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let number: u32 = number.parse().map_err(|_| invalid())?;
+        if year < 1 || !(1..=4).contains(&number) {
+            return Err(invalid().into());
+        }
+        let fiscal_year = Self::new(&format!("{year}{start_month:02}"), start_month)?;
+        let from = fiscal_year.start + Months::new((number - 1) * 3);
+        Ok(FiscalPeriod {
+            start: fiscal_year.start,
+            from,
+            month: from + Months::new(2),
+        })
+    }
+
+    /// The same period, but reporting from month `from` (`yyyymm`) on,
+    /// which must not lie before the start of the fiscal year nor after the closing month.
+    pub(super) fn starting_from(&self, from: &str) -> Result<Self, Box<dyn Error>> {
+        let from = parse_month(from)?;
+        if from < self.start || from > self.month {
+            return Err(format!(
+                "Invalid period from {} to {}, expected both months within the fiscal year starting {}",
+                from.format("%Y%m"),
+                self.month.format("%Y%m"),
+                self.start.format("%Y%m")
+            )
+            .into());
+        }
+        Ok(FiscalPeriod {
+            from,
+            ..self.clone()
+        })
+    }
+
+    /// The first day of the fiscal year, e.g. `2025-07-01`
+    pub(super) fn start_date(&self) -> String {
+        self.start.format("%Y-%m-%d").to_string()
+    }
+
+    /// The first day of the period, e.g. `2025-04-01` for the second quarter of the calendar year
+    pub(super) fn first_date(&self) -> String {
+        self.from.format("%Y-%m-%d").to_string()
+    }
+
+    /// Tells if the period covers the fiscal year from its start, i.e. reports year-to-date figures
+    pub(super) fn is_year_to_date(&self) -> bool {
+        self.from == self.start
+    }
+
+    /// The months of the period (`yyyymm`) for file names, e.g. `202505`, or `202504-202506`
+    /// for a period not starting with the fiscal year
+    pub(super) fn file_label(&self) -> String {
+        if self.is_year_to_date() {
+            self.closing_month()
+        } else {
+            format!("{}-{}", self.from.format("%Y%m"), self.closing_month())
+        }
+    }
+
+    /// The closing month (`yyyymm`), i.e. the last month of the period, e.g. `202506` for 2025Q2
+    pub(super) fn closing_month(&self) -> String {
+        self.month.format("%Y%m").to_string()
+    }
+
+    /// The first day to be ignored, e.g. `2025-08-01` for closing month 202507
+    pub(super) fn cut_off_date(&self) -> String {
+        (self.month + Months::new(1)).format("%Y-%m-%d").to_string()
+    }
+
+    /// The first day after the fiscal year
+    pub(super) fn end_of_year_date(&self) -> String {
+        (self.start + Months::new(12))
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    /// Boolean expression telling if the `Date` lies within the period
+    pub(super) fn contains_date(&self) -> Expr {
+        col("Date")
+            .gt_eq(lit(self.first_date()))
+            .and(col("Date").lt(lit(self.cut_off_date())))
+    }
+
+    /// The number of months of the period
+    pub(super) fn months(&self) -> u32 {
+        self.fiscal_months().len().try_into().unwrap_or_default()
+    }
+
+    /// The months of the period as indexes into the months of the fiscal year (`0` to `11`),
+    /// e.g. `3..6` for the second quarter
+    pub(super) fn fiscal_months(&self) -> Range<usize> {
+        let first = months_between(self.start, self.from);
+        first..months_between(self.start, self.month) + 1
+    }
+
+    /// The months of the period (`yyyy-mm`)
+    pub(super) fn month_labels(&self) -> Vec<String> {
+        (0..self.months())
+            .map(|m| (self.from + Months::new(m)).format("%Y-%m").to_string())
+            .collect()
+    }
+
+    /// The key of the fiscal year in the budget, i.e. the year it starts in
+    pub(super) fn budget_year(&self) -> String {
+        self.start.year().to_string()
+    }
+
+    /// The label of the fiscal year, e.g. `2025`, or `2025/26` if it does not match the calendar year
+    pub(super) fn label(&self) -> String {
+        if self.start.month() == 1 {
+            self.budget_year()
+        } else {
+            format!("{}/{:02}", self.start.year(), (self.start.year() + 1) % 100)
+        }
+    }
+
+    /// The label of the column with the figures of the period, e.g. `1.1.-31.5.25` for closing month 202505,
+    /// or `1.7.24-31.1.25` for a fiscal year starting in July and closing month 202501
+    pub(super) fn period_label(&self) -> String {
+        let last_day = self.month + Months::new(1) - chrono::Duration::days(1);
+        if self.from.year() == last_day.year() {
+            format!(
+                "{}-{}",
+                self.from.format("%-d.%-m."),
+                last_day.format("%-d.%-m.%y")
+            )
+        } else {
+            format!(
+                "{}-{}",
+                self.from.format("%-d.%-m.%y"),
+                last_day.format("%-d.%-m.%y")
+            )
+        }
+    }
+
+    /// The same period of the previous fiscal year
+    pub(super) fn previous(&self) -> Self {
+        FiscalPeriod {
+            start: self.start - Months::new(12),
+            from: self.from - Months::new(12),
+            month: self.month - Months::new(12),
+        }
+    }
+
+    /// The whole fiscal year, i.e. from its first up to and including its last month
+    pub(super) fn full_year(&self) -> Self {
+        FiscalPeriod {
+            start: self.start,
+            from: self.start,
+            month: self.start + Months::new(11),
+        }
+    }
+
+    /// The period from the start of the fiscal year up to and including the closing month
+    pub(super) fn year_to_date(&self) -> Self {
+        FiscalPeriod {
+            from: self.start,
+            ..self.clone()
+        }
+    }
+}
+
+/// Parses the `month` (`yyyymm`) into its first day
+fn parse_month(month: &str) -> Result<NaiveDate, Box<dyn Error>> {
+    let year: i32 = month.get(0..4).ok_or("Invalid month")?.parse()?;
+    let month_of_year: u32 = month.get(4..6).ok_or("Invalid month")?.parse()?;
+    Ok(NaiveDate::from_ymd_opt(year, month_of_year, 1)
+        .ok_or_else(|| format!("Invalid month {year}{month_of_year:02}"))?)
+}
+
+/// The number of months from the month of `first` to the month of `last`
+fn months_between(first: NaiveDate, last: NaiveDate) -> usize {
+    let months = (last.year() - first.year()) * 12
+        + i32::try_from(last.month()).unwrap_or_default()
+        - i32::try_from(first.month()).unwrap_or_default();
+    usize::try_from(months).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("202411", 1, "2024-01-01", "2024-12-01", 11)]
+    #[case("202412", 1, "2024-01-01", "2025-01-01", 12)]
+    #[case("202507", 1, "2025-01-01", "2025-08-01", 7)]
+    #[case("202507", 7, "2025-07-01", "2025-08-01", 1)]
+    #[case("202601", 7, "2025-07-01", "2026-02-01", 7)]
+    #[case("202506", 7, "2024-07-01", "2025-07-01", 12)]
+    fn test_period(
+        #[case] month: &str,
+        #[case] start_month: u32,
+        #[case] start_date: &str,
+        #[case] cut_off_date: &str,
+        #[case] months: u32,
+    ) {
+        let period = FiscalPeriod::new(month, start_month).expect("valid period");
+        assert_eq!(period.start_date(), start_date);
+        assert_eq!(period.cut_off_date(), cut_off_date);
+        assert_eq!(period.months(), months);
+    }
+
+    #[rstest]
+    #[case("202505", 1, "2025", "1.1.-31.5.25", "2026-01-01")]
+    #[case("202510", 7, "2025/26", "1.7.-31.10.25", "2026-07-01")]
+    #[case("202601", 7, "2025/26", "1.7.25-31.1.26", "2026-07-01")]
+    #[case("209912", 10, "2099/00", "1.10.-31.12.99", "2100-10-01")]
+    fn test_labels(
+        #[case] month: &str,
+        #[case] start_month: u32,
+        #[case] label: &str,
+        #[case] ytd_label: &str,
+        #[case] end_of_year_date: &str,
+    ) {
+        let period = FiscalPeriod::new(month, start_month).expect("valid period");
+        assert_eq!(period.label(), label);
+        assert_eq!(period.period_label(), ytd_label);
+        assert_eq!(period.end_of_year_date(), end_of_year_date);
+    }
+
+    #[rstest]
+    fn can_derive_previous_and_full_year() {
+        let period = FiscalPeriod::new("202601", 7).expect("valid period");
+        assert_eq!(
+            period.month_labels(),
+            vec![
+                "2025-07", "2025-08", "2025-09", "2025-10", "2025-11", "2025-12", "2026-01"
+            ]
+        );
+        assert_eq!(period.budget_year(), "2025");
+        let previous = period.previous();
+        assert_eq!(
+            previous,
+            FiscalPeriod::new("202501", 7).expect("valid period")
+        );
+        assert_eq!(previous.budget_year(), "2024");
+        let full_year = previous.full_year();
+        assert_eq!(
+            full_year,
+            FiscalPeriod::new("202506", 7).expect("valid period")
+        );
+        assert_eq!(full_year.months(), 12);
+    }
+
+    #[rstest]
+    #[case(FiscalPeriod::quarter("2025Q2", 1), "2025-04-01", "2025-07-01", 3..6, "202504-202506")]
+    #[case(FiscalPeriod::quarter("2025Q3", 7), "2026-01-01", "2026-04-01", 6..9, "202601-202603")]
+    #[case(FiscalPeriod::new("202505", 1).and_then(|p| p.starting_from("202505")), "2025-05-01", "2025-06-01", 4..5, "202505-202505")]
+    #[case(FiscalPeriod::new("202602", 7).and_then(|p| p.starting_from("202511")), "2025-11-01", "2026-03-01", 4..8, "202511-202602")]
+    #[case(FiscalPeriod::new("202505", 1).and_then(|p| p.starting_from("202501")), "2025-01-01", "2025-06-01", 0..5, "202505")]
+    fn test_reporting_period(
+        #[case] period: Result<FiscalPeriod, Box<dyn Error>>,
+        #[case] first_date: &str,
+        #[case] cut_off_date: &str,
+        #[case] fiscal_months: Range<usize>,
+        #[case] file_label: &str,
+    ) {
+        let period = period.expect("valid period");
+        assert_eq!(period.first_date(), first_date);
+        assert_eq!(period.cut_off_date(), cut_off_date);
+        assert_eq!(period.fiscal_months(), fiscal_months);
+        assert_eq!(period.file_label(), file_label);
+        assert!(file_label.ends_with(&period.closing_month()));
+    }
+
+    #[rstest]
+    fn can_label_quarter_and_derive_previous_year() {
+        let period = FiscalPeriod::quarter("2025Q2", 7).expect("valid quarter");
+        assert_eq!(period.period_label(), "1.10.-31.12.25");
+        assert_eq!(period.label(), "2025/26");
+        assert_eq!(period.months(), 3);
+        assert_eq!(period.month_labels(), vec!["2025-10", "2025-11", "2025-12"]);
+        assert!(!period.is_year_to_date());
+        assert_eq!(
+            period.previous(),
+            FiscalPeriod::quarter("2024Q2", 7).expect("valid quarter")
+        );
+        assert_eq!(
+            period.full_year(),
+            FiscalPeriod::new("202606", 7).expect("valid period")
+        );
+        assert_eq!(
+            period.year_to_date(),
+            FiscalPeriod::new("202512", 7).expect("valid period")
+        );
+    }
+
+    #[rstest]
+    #[case("2025Q0")]
+    #[case("2025Q5")]
+    #[case("2025-2")]
+    #[case("Q2")]
+    fn rejects_invalid_quarter(#[case] quarter: &str) {
+        assert!(FiscalPeriod::quarter(quarter, 1).is_err());
+    }
+
+    #[rstest]
+    #[case("202506", 1, "202507")]
+    #[case("202506", 1, "202412")]
+    #[case("202510", 7, "202506")]
+    fn rejects_period_outside_fiscal_year(
+        #[case] month: &str,
+        #[case] start_month: u32,
+        #[case] from: &str,
+    ) {
+        let period = FiscalPeriod::new(month, start_month).expect("valid period");
+        assert!(period.starting_from(from).is_err());
+    }
+
+    #[rstest]
+    #[case("202505", 0)]
+    #[case("202505", 13)]
+    #[case("2025", 1)]
+    #[case("202513", 1)]
+    fn rejects_invalid_period(#[case] month: &str, #[case] start_month: u32) {
+        assert!(FiscalPeriod::new(month, start_month).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::close::budget::Budget;
+use crate::close::close_xml::{CREDIT_MISSING, DEBIT_MISSING};
+use crate::close::fiscal_year::FiscalPeriod;
+use crate::money::zero;
+use polars::prelude::*;
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::Arc;
+
+/// The name of the worksheet listing the findings of the integrity checks
+pub const INTEGRITY_CHECKS: &str = "Prüfungen";
+
+const DEBIT_ACCOUNT_MISSING: &str = "Soll-Konto fehlt";
+const CREDIT_ACCOUNT_MISSING: &str = "Haben-Konto fehlt";
+const DEBIT_ACCOUNT_UNKNOWN: &str = "Soll-Konto unbekannt";
+const CREDIT_ACCOUNT_UNKNOWN: &str = "Haben-Konto unbekannt";
+const INVALID_AMOUNT: &str = "Betrag ungültig";
+const ZERO_AMOUNT: &str = "Betrag ist 0";
+const INVALID_DATE: &str = "Datum fehlt/ungültig";
+const OUTSIDE_FISCAL_YEAR: &str = "Datum ausserhalb des Geschäftsjahres";
+const LARGE_AMOUNT: &str = "Grosser Betrag";
+
+/// The remarks of the integrity checks, in the order they are checked
+const CHECKS: [&str; 9] = [
+    DEBIT_ACCOUNT_MISSING,
+    CREDIT_ACCOUNT_MISSING,
+    DEBIT_ACCOUNT_UNKNOWN,
+    CREDIT_ACCOUNT_UNKNOWN,
+    INVALID_AMOUNT,
+    ZERO_AMOUNT,
+    INVALID_DATE,
+    OUTSIDE_FISCAL_YEAR,
+    LARGE_AMOUNT,
+];
+
+/// Checks the integrity of the journal lines, listing the lines with a missing debit or credit account,
+/// with accounts not present in `accounts` (if there are any), with an unparseable (null) or a zero amount,
+/// with a missing or invalid (null) date, dated outside the fiscal year of the period or with an amount of at least
+/// `[close] large_amount` of the budget configuration.
+/// A line failing several checks is listed once per check, with the remark in column `Check`.
+pub fn check_integrity(
+    journal: &DataFrame,
+    accounts: &DataFrame,
+    budget: &Budget,
+    period: &FiscalPeriod,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let (first_day, next_first_day) = (period.start_date(), period.end_of_year_date());
+    let known: Arc<HashSet<String>> = Arc::new(
+        accounts
+            .column("Account")?
+            .str()?
+            .into_no_null_iter()
+            .map(String::from)
+            .collect(),
+    );
+    let large_amount = budget.close.large_amount;
+
+    let predicates = [
+        col("Debit").eq(lit(DEBIT_MISSING)),
+        col("Credit").eq(lit(CREDIT_MISSING)),
+        unknown_account("Debit", DEBIT_MISSING, &known),
+        unknown_account("Credit", CREDIT_MISSING, &known),
+        col("Amount").is_null(),
+        col("Amount").eq(zero()),
+        col("Date").is_null(),
+        col("Date")
+            .lt(lit(first_day))
+            .or(col("Date").gt_eq(lit(next_first_day))),
+        col("Amount")
+            .gt_eq(lit(large_amount))
+            .or(col("Amount").lt_eq(lit(-large_amount))),
+    ];
+    let lines = journal.clone().lazy().with_row_index("Row", None);
+    let findings = predicates
+        .into_iter()
+        .zip(CHECKS)
+        .map(|(predicate, check)| {
+            lines
+                .clone()
+                .filter(predicate)
+                .with_column(lit(check).alias("Check"))
+        });
+    let checks = concat(findings.collect::<Vec<_>>(), UnionArgs::default())?
+        .sort(
+            ["Row"],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )
+        .select([
+            col("Date"),
+            col("Description"),
+            col("Debit"),
+            col("Credit"),
+            col("Amount"),
+            col("Check"),
+        ])
+        .collect()?;
+    Ok(checks)
+}
+
+/// Boolean expression telling if the account of the `side` is neither missing nor in `known`.
+/// Without any `known` accounts (e.g. if the sheet Accounts is missing), no account is unknown.
+fn unknown_account(side: &str, missing: &'static str, known: &Arc<HashSet<String>>) -> Expr {
+    if known.is_empty() {
+        return lit(false);
+    }
+    let known = known.clone();
+    col(side).map(
+        move |a| {
+            Ok(a.str()?
+                .into_iter()
+                .map(|a| a.map(|a| a != missing && !known.contains(a)))
+                .collect::<BooleanChunked>()
+                .into_column())
+        },
+        |_, field| Ok(Field::new(field.name().clone(), DataType::Boolean)),
+    )
+}
+
+/// Summarises the findings of the integrity checks per check for the console.
+pub fn summarize(checks: &DataFrame) -> Result<String, Box<dyn Error>> {
+    if checks.height() == 0 {
+        return Ok("No journal integrity issues found.".into());
+    }
+    let remarks = checks.column("Check")?.str()?;
+    let counts = CHECKS.into_iter().filter_map(|check| {
+        let count = remarks.into_iter().filter(|r| *r == Some(check)).count();
+        (count > 0).then(|| format!("\n  {check}: {count}"))
+    });
+    Ok(format!(
+        "Found {} journal integrity issue(s), see worksheet {INTEGRITY_CHECKS}:{}",
+        checks.height(),
+        counts.collect::<String>()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close::budget::read_budget_config;
+    use crate::money::as_money;
+    use crate::test_fixtures::{accounts_df_01, journal_df_01};
+    use crate::test_utils::assert_dataframe;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn can_check_integrity(journal_df_01: DataFrame, accounts_df_01: DataFrame) {
+        let budget =
+            read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget");
+        let issues = df!(
+            "Date" => &[Some("2024-12-31"), Some("2025-08-01"), Some("2025-09-15"), None, Some("2025-08-02")],
+            "Description" => &["Vorjahr", "Ohne Haben", "Unbekannt", "Ohne Datum", "Betrag falsch"],
+            "Debit" => &["10100", "10100", "99999", "10100", "10100"],
+            "Credit" => &["30100", CREDIT_MISSING, "30100", "30100", "30100"],
+            "Amount" => &[Some(50.0), Some(0.0), Some(12000.0), Some(20.0), None],
+            "Cc1" => &["", "", "", "", ""],
+            "Cc2" => &["", "", "", "", ""],
+            "Cc3" => &["", "", "", "", ""],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid journal dataframe");
+        let journal = journal_df_01.vstack(&issues).expect("can extend journal");
+        let expected = df!(
+            "Date" => &[
+                Some("2024-12-31"), Some("2025-08-01"), Some("2025-08-01"), Some("2025-09-15"),
+                Some("2025-09-15"), None, Some("2025-08-02"),
+            ],
+            "Description" => &[
+                "Vorjahr", "Ohne Haben", "Ohne Haben", "Unbekannt", "Unbekannt", "Ohne Datum",
+                "Betrag falsch",
+            ],
+            "Debit" => &["10100", "10100", "10100", "99999", "99999", "10100", "10100"],
+            "Credit" => &[
+                "30100", CREDIT_MISSING, CREDIT_MISSING, "30100", "30100", "30100", "30100",
+            ],
+            "Amount" => &[
+                Some(50.0), Some(0.0), Some(0.0), Some(12000.0), Some(12000.0), Some(20.0), None,
+            ],
+            "Check" => &[
+                OUTSIDE_FISCAL_YEAR, CREDIT_ACCOUNT_MISSING, ZERO_AMOUNT,
+                DEBIT_ACCOUNT_UNKNOWN, LARGE_AMOUNT, INVALID_DATE, INVALID_AMOUNT,
+            ],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid checks dataframe");
+
+        let period = budget.fiscal_period("202508").expect("valid period");
+        let actual = check_integrity(&journal, &accounts_df_01, &budget, &period)
+            .expect("can check integrity");
+        assert_dataframe(&actual, &expected);
+        assert_eq!(
+            summarize(&actual).expect("can summarize"),
+            "Found 7 journal integrity issue(s), see worksheet Prüfungen:\
+            \n  Haben-Konto fehlt: 1\
+            \n  Soll-Konto unbekannt: 1\
+            \n  Betrag ungültig: 1\
+            \n  Betrag ist 0: 1\
+            \n  Datum fehlt/ungültig: 1\
+            \n  Datum ausserhalb des Geschäftsjahres: 1\
+            \n  Grosser Betrag: 1"
+        );
+    }
+
+    #[rstest]
+    fn clean_journal_has_no_issues(journal_df_01: DataFrame, accounts_df_01: DataFrame) {
+        let budget =
+            read_budget_config(&PathBuf::from("samples/budget.toml")).expect("valid sample budget");
+        let period = budget.fiscal_period("202505").expect("valid period");
+        let actual = check_integrity(&journal_df_01, &accounts_df_01, &budget, &period)
+            .expect("can check integrity");
+        assert_eq!(actual.height(), 0);
+        assert_eq!(
+            summarize(&actual).expect("can summarize"),
+            "No journal integrity issues found."
+        );
+    }
+}
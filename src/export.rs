@@ -3,7 +3,7 @@ use chrono::NaiveDate;
 use polars::prelude::*;
 use polars_excel_writer::PolarsExcelWriter;
 use rust_xlsxwriter::Workbook;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::path::{Path, PathBuf};
 
@@ -12,35 +12,88 @@ use crate::export::constraint::{
     validation_topic_owner,
 };
 use crate::export::export_accounting::{
-    gather_df_accounting, validate_acc_constraint_and_calculate_correction_postings,
+    calculate_correction_postings, gather_df_accounting, validate_acc_constraint,
+};
+use crate::export::export_banana::{
+    ClearingAccounts, VatCodes, gather_df_banana, gather_df_banana_clearing,
+    gather_df_banana_details, number_postings, render_banana_import, validate_clearing_transfers,
 };
-use crate::export::export_banana::{gather_df_banana, gather_df_banana_details};
 use crate::export::export_details::collect_data;
+use crate::export::export_ledger::{AccountNames, PlainTextFormat, render_plain_text};
 use crate::export::export_miti::gather_df_miti;
+use crate::money::{MONEY, as_float};
 use crate::prepare::{Topic, warn_on_zero_value_trx};
 
+pub use crate::export::export_banana::BelegNumbering;
+
 mod constraint;
 mod export_accounting;
 mod export_banana;
 mod export_details;
+mod export_ledger;
 mod export_miti;
 mod posting;
 
 const EXCEL_EPOCH_OFFSET: i32 = 25569;
 const MS_PER_DAY: f64 = 86_400_000_000_000f64;
 
+/// The optional tables of the export
+#[derive(Debug, Default)]
+pub struct ExportOptions {
+    /// Whether to write the banana postings as plain-text journals (ledger/hledger and beancount)
+    pub plain_text: bool,
+    /// The TOML file with the names of the accounts in the plain-text journals
+    pub account_names: Option<PathBuf>,
+    /// The TOML file with the VAT codes of the accounts in the banana import file
+    pub vat_codes: Option<PathBuf>,
+    /// The TOML file with the clearing accounts of the payment instruments (e.g. `TWINT = "10921"`)
+    pub clearing_accounts: Option<PathBuf>,
+    /// How the postings of the banana export are numbered
+    pub numbering: BelegNumbering,
+}
+
 /// Reads the intermediate files and exports all configured reports.
-pub fn export(input_path: &Path, month: &str, ts: &str) -> Result<(), Box<dyn Error>> {
+pub fn export(
+    input_path: &Path,
+    month: &str,
+    options: &ExportOptions,
+    ts: &str,
+) -> Result<(), Box<dyn Error>> {
+    let account_names: AccountNames = read_account_table(options.account_names.as_deref())?;
+    let vat_codes: Option<VatCodes> = options
+        .vat_codes
+        .as_deref()
+        .map(|path| read_account_table(Some(path)))
+        .transpose()?;
+    let clearing_accounts: ClearingAccounts =
+        read_account_table(options.clearing_accounts.as_deref())?;
     let raw_df = read_intermediate_from_excel(input_path, month)?;
 
     warn_on_zero_value_trx(&raw_df)?;
 
-    let (df_det, df_acc, df_banana, raw_df_corr) = crunch_data(&raw_df.clone(), month)?;
-
-    export_details(month, ts, &df_det, &raw_df_corr)?;
+    let (df_det, df_acc, df_banana, raw_df_corr) = crunch_data(
+        &raw_df.clone(),
+        month,
+        &clearing_accounts,
+        &options.numbering,
+    )?;
+    let belege = df_banana.select([
+        "Beleg",
+        "Datum",
+        "Beschreibung",
+        "KtSoll",
+        "KtHaben",
+        "Betrag CHF",
+    ])?;
+
+    export_details(month, ts, &df_det, &raw_df_corr, &belege)?;
     export_mittagstisch(month, ts, &df_det, &raw_df_corr)?;
-    export_accounting(month, ts, &df_acc, &raw_df_corr)?;
-    export_banana(month, ts, &df_banana, &raw_df_corr)
+    export_accounting(month, ts, &df_acc, &raw_df_corr, &belege)?;
+    export_banana(month, ts, &df_banana, &raw_df_corr, vat_codes.as_ref())?;
+    if options.plain_text {
+        export_plain_text(month, ts, &df_banana, &account_names)?;
+    }
+    Ok(())
 }
 
 #[allow(clippy::too_many_lines)]
@@ -48,7 +101,12 @@ fn read_intermediate_from_excel(
     input_path: &Path,
     month: &str,
 ) -> Result<DataFrame, Box<dyn Error>> {
-    let columns_vec = read_columns_from_excel(input_path, month)?;
+    let mut columns_vec = read_columns_from_excel(input_path, month)?;
+    // intermediate files prepared before the payment instrument was kept do not have the column
+    if !columns_vec.iter().any(|c| c.name() == "Payment Instrument") {
+        let rows = columns_vec.first().map_or(0, Column::len);
+        columns_vec.push(Column::new("Payment Instrument".into(), vec![""; rows]));
+    }
     let df = DataFrame::new(columns_vec)?
         .lazy()
         .with_column(
@@ -117,8 +175,9 @@ fn read_intermediate_from_excel(
                             .collect();
                         Ok(parsed.into_column())
                     },
-                    |_, field| Ok(Field::new(field.name().clone(), DataType::Int32)),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::Float64)),
                 )
+                .cast(MONEY)
                 .alias("Price (Gross)"),
         )
         .with_column(
@@ -132,8 +191,9 @@ fn read_intermediate_from_excel(
                             .collect();
                         Ok(parsed.into_column())
                     },
-                    |_, field| Ok(Field::new(field.name().clone(), DataType::Int32)),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::Float64)),
                 )
+                .cast(MONEY)
                 .alias("Price (Net)"),
         )
         .with_column(
@@ -147,11 +207,13 @@ fn read_intermediate_from_excel(
                             .collect();
                         Ok(parsed.into_column())
                     },
-                    |_, field| Ok(Field::new(field.name().clone(), DataType::Int32)),
+                    |_, field| Ok(Field::new(field.name().clone(), DataType::Float64)),
                 )
+                .cast(MONEY)
                 .alias("Commission"),
         )
         .with_column(col("Payment Method").str().strip_chars(lit(" ")))
+        .with_column(col("Payment Instrument").str().strip_chars(lit(" ")))
         .with_column(col("Topic").str().strip_chars(lit(" ")))
         .with_column(col("Owner").str().strip_chars(lit(" ")))
         .with_column(col("Purpose").str().strip_chars(lit(" ")))
@@ -190,11 +252,14 @@ fn read_columns_from_excel(input_path: &Path, month: &str) -> Result<Vec<Column>
 /// Returns for the raw intermediate dataframe `raw_df` and `month` returns four dataframes:
 /// - details/miti
 /// - accounting export
-/// - banana (filterd and enriched in regards of trx that need individual reporting instead of summary)
+/// - banana (filterd and enriched in regards of trx that need individual reporting instead of summary),
+///   with the transfers to the `clearing_accounts` of the payment instruments, numbered according to `numbering`
 /// - `raw_df_corr`: The raw data frame passed in enriched with potential rounding corrections
 fn crunch_data(
     raw_df: &DataFrame,
     month: &str,
+    clearing_accounts: &ClearingAccounts,
+    numbering: &BelegNumbering,
 ) -> Result<(DataFrame, DataFrame, DataFrame, DataFrame), Box<dyn Error>> {
     validate(raw_df)?;
 
@@ -203,11 +268,16 @@ fn crunch_data(
     let df_det_extended =
         df_det.sort(["Date"], SortMultipleOptions::new().with_nulls_last(true))?;
     let df_acc = gather_df_accounting(&df_det_extended)?;
-    let mut df_banana_summary = gather_df_banana(&df_acc.clone(), month)?;
-    let correction = validate_acc_constraint_and_calculate_correction_postings(&df_acc, month)?;
-    df_banana_summary.extend(&correction)?;
+    validate_acc_constraint(&df_acc)?;
+    let df_banana_summary = gather_df_banana(&df_acc.clone(), month)?;
     let df_banana_details = gather_df_banana_details(raw_df)?;
-    let df_banana = filter_and_enrich_banana(&df_banana_summary, &df_banana_details)?;
+    let mut df_banana = filter_and_enrich_banana(&df_banana_summary, &df_banana_details)?;
+    // the corrections net the postings as imported, i.e. after replacing summaries by details
+    df_banana.extend(&calculate_correction_postings(&df_acc, &df_banana, month)?)?;
+    let transfers = gather_df_banana_clearing(raw_df, month, clearing_accounts)?;
+    validate_clearing_transfers(&transfers, &df_acc)?;
+    df_banana.extend(&transfers)?;
+    let df_banana = number_postings(&df_banana, month, numbering)?;
     Ok((df_det_extended, df_acc, df_banana, raw_df.clone()))
 }
 
@@ -239,8 +309,9 @@ fn export_details(
     ts: &str,
     df: &DataFrame,
     df_trx: &DataFrame,
+    belege: &DataFrame,
 ) -> Result<(), Box<dyn Error>> {
-    write_to_file(df, df_trx, "details", month, ts)?;
+    write_to_file(df, df_trx, Some(belege), "details", month, ts)?;
     Ok(())
 }
 
@@ -256,7 +327,7 @@ fn export_mittagstisch(
         .lazy()
         .filter(col("Topic").eq(lit(Topic::MiTi.to_string())))
         .collect()?;
-    write_to_file(&df_miti, &df_miti_trx, "mittagstisch", month, ts)?;
+    write_to_file(&df_miti, &df_miti_trx, None, "mittagstisch", month, ts)?;
     Ok(())
 }
 
@@ -265,8 +336,9 @@ fn export_accounting(
     ts: &str,
     df_acc: &DataFrame,
     df_trx: &DataFrame,
+    belege: &DataFrame,
 ) -> Result<(), Box<dyn Error>> {
-    write_to_file(df_acc, df_trx, "accounting", month, ts)
+    write_to_file(df_acc, df_trx, Some(belege), "accounting", month, ts)
 }
 
 /// filters out the summary records for accounts that need individual trx
@@ -311,13 +383,52 @@ fn filter_and_enrich_banana(
     }
 }
 
+/// Writes the banana export to be copied into the journal of Banana,
+/// and the same postings as text file to be imported into Banana.
 fn export_banana(
     month: &str,
     ts: &str,
     df_banana: &DataFrame,
     df_trx: &DataFrame,
+    vat_codes: Option<&VatCodes>,
+) -> Result<(), Box<dyn Error>> {
+    write_to_file(df_banana, df_trx, None, "banana", month, ts)?;
+    let import = render_banana_import(df_banana, vat_codes)?;
+    std::fs::write(
+        path_with_prefix("banana", month, ts).with_extension("txt"),
+        import,
+    )?;
+    Ok(())
+}
+
+/// Reads a table of accounts from a TOML file (e.g. `10000 = "Assets:Kasse"`),
+/// empty if no file is provided.
+fn read_account_table(path: Option<&Path>) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let Some(path) = path else {
+        return Ok(HashMap::new());
+    };
+    let toml_str = std::fs::read_to_string(path)?;
+    let table = toml::from_str(&toml_str)
+        .map_err(|e| format!("Invalid account table in {}: {e}", path.display()))?;
+    Ok(table)
+}
+
+/// Writes the postings of the banana export as ledger/hledger journal and as beancount file.
+fn export_plain_text(
+    month: &str,
+    ts: &str,
+    df_banana: &DataFrame,
+    account_names: &AccountNames,
 ) -> Result<(), Box<dyn Error>> {
-    write_to_file(df_banana, df_trx, "banana", month, ts)
+    for format in [PlainTextFormat::Ledger, PlainTextFormat::Beancount] {
+        let (prefix, extension) = format.file_name_parts();
+        let journal = render_plain_text(df_banana, account_names, month, format)?;
+        std::fs::write(
+            path_with_prefix(prefix, month, ts).with_extension(extension),
+            journal,
+        )?;
+    }
+    Ok(())
 }
 
 /// Constructs a path for an XLSX file from `prefix`, `month` and `ts` (timestamp).
@@ -325,16 +436,18 @@ pub fn path_with_prefix(prefix: &str, month: &str, ts: &str) -> PathBuf {
     PathBuf::from(format!("{prefix}_{month}_{ts}.xlsx"))
 }
 
-/// Writes the dataframe `df` to the file system into path `path`.
+/// Writes the dataframe `df` to the file system into path `path`,
+/// followed by the numbered banana postings (`belege`) if provided for cross-referencing.
 fn write_to_file(
     main_df: &DataFrame,
     trx_df: &DataFrame,
+    belege: Option<&DataFrame>,
     prefix: &str,
     month: &str,
     ts: &str,
 ) -> Result<(), Box<dyn Error>> {
     // work around https://github.com/jmcnamara/polars_excel_writer/issues/26
-    let mut trx_df = trx_df.clone();
+    let mut trx_df = as_float(trx_df)?;
     trx_df.rechunk_mut();
 
     let path = &path_with_prefix(prefix, month, ts);
@@ -349,13 +462,20 @@ fn write_to_file(
     let worksheet = workbook.add_worksheet().set_name(prefix)?;
     excel_writer.set_freeze_panes(1, 1);
 
-    excel_writer.write_dataframe_to_worksheet(main_df, worksheet, 0, 0)?;
+    excel_writer.write_dataframe_to_worksheet(&as_float(main_df)?, worksheet, 0, 0)?;
 
     let worksheet = workbook.add_worksheet().set_name("transaktionen")?;
     excel_writer.set_freeze_panes(1, 3);
     excel_writer.write_dataframe_to_worksheet(&trx_df, worksheet, 0, 0)?;
     excel_writer.set_column_format("Time", "HH:MM:SS");
 
+    if let Some(belege) = belege {
+        let worksheet = workbook.add_worksheet().set_name("belege")?;
+        excel_writer.set_freeze_panes(1, 1);
+        excel_writer.set_column_format("Datum", "dd.mm.YYYY");
+        excel_writer.write_dataframe_to_worksheet(&as_float(belege)?, worksheet, 0, 0)?;
+    }
+
     workbook.save(path)?;
     Ok(())
 }
@@ -377,9 +497,10 @@ mod tests {
     use pretty_assertions::assert_ne;
     use rstest::rstest;
 
+    use crate::money::as_money;
     use crate::test_fixtures::{
         accounting_df_06, banana_df_ext_06, details_df_04, intermediate_df_02, intermediate_df_04,
-        intermediate_df_06,
+        intermediate_df_06, sample_date, sample_date_lom,
     };
 
     use super::*;
@@ -387,8 +508,13 @@ mod tests {
     #[rstest]
     fn can_crunch_data_without_panic(intermediate_df_02: DataFrame) {
         println!("{intermediate_df_02:?}");
-        let (df1, df2, df3, df4) =
-            crunch_data(&intermediate_df_02, "202412").expect("should crunch");
+        let (df1, df2, df3, df4) = crunch_data(
+            &intermediate_df_02,
+            "202412",
+            &ClearingAccounts::new(),
+            &BelegNumbering::default(),
+        )
+        .expect("should crunch");
 
         assert_ne!(df1.shape().0, 0, "df1 does not contain records");
         assert_ne!(df2.shape().0, 0, "df2 does not contain records");
@@ -399,7 +525,13 @@ mod tests {
     #[rstest]
     fn can_calculate_summary_row(intermediate_df_04: DataFrame, details_df_04: DataFrame) {
         configure_the_environment();
-        let (df1, _, _, _) = crunch_data(&intermediate_df_04, "202412").expect("should crunch");
+        let (df1, _, _, _) = crunch_data(
+            &intermediate_df_04,
+            "202412",
+            &ClearingAccounts::new(),
+            &BelegNumbering::default(),
+        )
+        .expect("should crunch");
         assert_eq!(
             df1.shape().0,
             4,
@@ -439,4 +571,56 @@ mod tests {
             .expect("Unable to get filtered and enriched df");
         assert_dataframe(&out, &banana_df_ext_06);
     }
+
+    #[rstest]
+    fn given_debit_surplus_on_20121_when_correcting_we_should_keep_its_correction(
+        accounting_df_06: DataFrame,
+        sample_date: NaiveDate,
+        sample_date_lom: NaiveDate,
+    ) {
+        let mut acct_df = accounting_df_06.clone();
+        acct_df
+            .extend(
+                &accounting_df_06
+                    .lazy()
+                    .sum()
+                    .collect()
+                    .expect("Should be able to sum accounting_df_06"),
+            )
+            .expect("Should be able to extend accounting_df_06");
+        let df_banana_summary =
+            gather_df_banana(&acct_df, "202303").expect("Unable to get banana df");
+        let df_banana_details = df!(
+            "Datum" => [sample_date, sample_date],
+            "Beleg" => ["", ""],
+            "Rechnung" => ["", ""],
+            "Beschreibung" => ["SU Kooperation bar", "SU Kooperation Ausbezahlt"],
+            "KtSoll" => ["10000", "20121"],
+            "KtHaben" => ["20121", "10000"],
+            "Anzahl" => ["", ""],
+            "Einheit" => ["", ""],
+            "Preis/Einheit" => ["", ""],
+            "Betrag CHF" => [50.0, 50.02],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid banana details");
+        let df_banana = filter_and_enrich_banana(&df_banana_summary, &df_banana_details)
+            .expect("Unable to get filtered and enriched df");
+
+        let corrections = calculate_correction_postings(&acct_df, &df_banana, "202303")
+            .expect("differences are within tolerance");
+        let expected = df!(
+            "Datum" => [sample_date_lom],
+            "Beschreibung" => ["SU Rundungskorrektur 20121"],
+            "KtSoll" => ["68450"],
+            "KtHaben" => ["20121"],
+            "Betrag CHF" => [0.02],
+        )
+        .and_then(|df| as_money(&df))
+        .expect("valid correction");
+        let actual = corrections
+            .select(["Datum", "Beschreibung", "KtSoll", "KtHaben", "Betrag CHF"])
+            .expect("correction columns");
+        assert_dataframe(&actual, &expected);
+    }
 }
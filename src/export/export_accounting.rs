@@ -4,6 +4,7 @@ use std::error::Error;
 use polars::prelude::*;
 
 use crate::export::posting::Posting;
+use crate::money::{amount, as_float, zero};
 
 /// Produces the Accounting dataframe from the details [df]
 pub fn gather_df_accounting(df_det: &DataFrame) -> PolarsResult<DataFrame> {
@@ -11,30 +12,30 @@ pub fn gather_df_accounting(df_det: &DataFrame) -> PolarsResult<DataFrame> {
         .clone()
         .lazy()
         .with_column(
-            (col("Net Card Total").fill_null(0.0) + col("Tips_Card").fill_null(0.0))
+            (col("Net Card Total").fill_null(zero()) + col("Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Payment SumUp"),
         )
         .with_column(
-            (col("Net Card MiTi").fill_null(0.0) + col("MiTi_Tips_Card").fill_null(0.0))
+            (col("Net Card MiTi").fill_null(zero()) + col("MiTi_Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias(Posting::NET_CARD_TOTAL_MITI.column_name),
         )
         .with_column(
-            (col("Tips_Card").fill_null(0.0) - col("MiTi_Tips_Card").fill_null(0.0))
+            (col("Tips_Card").fill_null(zero()) - col("MiTi_Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias(Posting::TIPS_CARD_LOLA.column_name),
         )
         .with_column(
-            (col("Gross Cash").fill_null(0.0)
-                - col("MiTi_Cash").fill_null(0.0)
-                - col("Culture (PaidOut) Total").fill_null(0.0))
+            (col("Gross Cash").fill_null(zero())
+                - col("MiTi_Cash").fill_null(zero())
+                - col("Culture (PaidOut) Total").fill_null(zero()))
             .round(2, RoundMode::HalfToEven)
             .alias("Total Cash Debit"),
         )
         .with_column(
-            (col("Gross Card LoLa").fill_null(0.0) + col("Tips_Card").fill_null(0.0)
-                - col("MiTi_Tips_Card").fill_null(0.0))
+            (col("Gross Card LoLa").fill_null(zero()) + col("Tips_Card").fill_null(zero())
+                - col("MiTi_Tips_Card").fill_null(zero()))
             .round(2, RoundMode::HalfToEven)
             .alias("Total Card Debit"),
         )
@@ -74,21 +75,73 @@ pub fn gather_df_accounting(df_det: &DataFrame) -> PolarsResult<DataFrame> {
         .collect()
 }
 
+/// A transitory (clearing) account of the accounting export expected to net to 0
+struct TransitoryAccount {
+    account: &'static str,
+    /// The net value of the account per row of the accounting export
+    net_expr: fn() -> Expr,
+    /// The net value (debit minus credit) of the movements of the account per row of the accounting export
+    /// not posted in the banana export
+    unposted_expr: fn() -> Expr,
+    /// The largest rounding difference tolerated per day and for the month
+    tolerance: &'static str,
+    /// The account the rounding correction of the month is booked against
+    counter_account: &'static str,
+}
+
+/// The transitory accounts, validated in this order
+const TRANSITORY_ACCOUNTS: [TransitoryAccount; 3] = [
+    TransitoryAccount {
+        account: "10920",
+        net_expr: net_expr_10920,
+        unposted_expr: unposted_expr_10920,
+        tolerance: "0.05",
+        counter_account: "68450",
+    },
+    TransitoryAccount {
+        account: "20121",
+        net_expr: net_expr_20121,
+        unposted_expr: zero,
+        tolerance: "0.05",
+        counter_account: "68450",
+    },
+    TransitoryAccount {
+        account: "20051",
+        net_expr: net_expr_20051,
+        unposted_expr: zero,
+        tolerance: "0.05",
+        counter_account: "68450",
+    },
+];
+
 /// Validates there are no excessive rounding differences as leftovers
-/// in transitory accounts. Where defined, it calculates and returns
-/// a correction posting that nets the balance.
-pub fn validate_acc_constraint_and_calculate_correction_postings(
+/// in transitory accounts on any day of the accounting export.
+pub fn validate_acc_constraint(df_acc: &DataFrame) -> Result<(), Box<dyn Error>> {
+    for transitory in &TRANSITORY_ACCOUNTS {
+        validate_constraint(df_acc, transitory)?;
+    }
+    Ok(())
+}
+
+/// Calculates the correction postings that net the balances of the transitory accounts of the month,
+/// considering the postings of the banana export (`df_banana`) as they are imported
+/// and the movements not posted there (the payments of sumup) of the summary row of the accounting export.
+/// Fails if the balance of the month exceeds the tolerance of the account.
+pub fn calculate_correction_postings(
     df_acc: &DataFrame,
+    df_banana: &DataFrame,
     month: &str,
 ) -> Result<DataFrame, Box<dyn Error>> {
-    validate_acc_constraint_10920(df_acc)?;
-    validate_acc_constraint_20121(df_acc)?;
-    validate_acc_constraint_and_calculate_correction_20051(df_acc, month)
+    let mut corrections = Vec::new();
+    for transitory in &TRANSITORY_ACCOUNTS {
+        corrections.push(calculate_correction(df_acc, df_banana, transitory, month)?.lazy());
+    }
+    Ok(concat(corrections, UnionArgs::default())?.collect()?)
 }
 
-/// validates the transitory account 10920 nets to 0
-fn validate_acc_constraint_10920(df_acc: &DataFrame) -> Result<(), Box<dyn Error>> {
-    let net_expr = col(Posting::CAFE_CARD.alias)
+/// the net value of the transitory account 10920 (debit minus credit)
+fn net_expr_10920() -> Expr {
+    col(Posting::CAFE_CARD.alias)
         + col(Posting::VERM_CARD.alias)
         + col(Posting::SOFE_CARD.alias)
         + col(Posting::DEPOSIT_CARD.alias)
@@ -99,92 +152,129 @@ fn validate_acc_constraint_10920(df_acc: &DataFrame) -> Result<(), Box<dyn Error
         + col(Posting::PAIDOUT_CARD.alias)
         + col(Posting::TIPS_CARD_LOLA.alias)
         - col("Payment SumUp")
-        - col(Posting::LOLA_COMMISSION.alias);
-    validate_constraint(df_acc, net_expr, "10920")?
+        - col(Posting::LOLA_COMMISSION.alias)
 }
 
-/// validates the transitory account 20121 nets to 0
-fn validate_acc_constraint_20121(df_acc: &DataFrame) -> Result<(), Box<dyn Error>> {
-    let net_expr = col(Posting::PAIDOUT_CASH.alias) + col(Posting::PAIDOUT_CARD.alias)
-        - col(Posting::PAIDOUT_TOTAL.alias);
-    validate_constraint(df_acc, net_expr, "20121")?
+/// the net value of the transitory account 20121 (credit minus debit)
+fn net_expr_20121() -> Expr {
+    col(Posting::PAIDOUT_CASH.alias) + col(Posting::PAIDOUT_CARD.alias)
+        - col(Posting::PAIDOUT_TOTAL.alias)
 }
 
-/// validates the transitory account 20051 nets to 0 and
-/// calculates the correction posting necessary to net account 20051 to 0
-fn validate_acc_constraint_and_calculate_correction_20051(
-    df_acc: &DataFrame,
-    month: &str,
-) -> Result<DataFrame, Box<dyn Error>> {
-    let net_expr = col(Posting::NET_CARD_TOTAL_MITI.alias)
+/// the net value of the transitory account 20051 (credit minus debit)
+fn net_expr_20051() -> Expr {
+    col(Posting::NET_CARD_TOTAL_MITI.alias)
         - col(Posting::DEBT_TO_MITI.alias)
         - col(Posting::INCOME_LOLA_MITI.alias)
-        + col(Posting::SPONSORED_REDUCTIONS.alias);
-    let _ = validate_constraint(df_acc, net_expr.clone(), "20051")?;
-    calculate_correction_20051(df_acc, &net_expr, month)
+        + col(Posting::SPONSORED_REDUCTIONS.alias)
 }
 
+/// validates the net value of the transitory account does not exceed its tolerance on any row
 fn validate_constraint(
     df_acc: &DataFrame,
-    net_expr: Expr,
-    account: &str,
-) -> Result<Result<(), Box<dyn Error>>, Box<dyn Error>> {
+    transitory: &TransitoryAccount,
+) -> Result<(), Box<dyn Error>> {
     let violations = df_acc
         .clone()
         .lazy()
-        .with_column(net_expr.alias("Net"))
+        .with_column((transitory.net_expr)().alias("Net"))
         .filter(
             col("Net")
                 .round(2, RoundMode::HalfToEven)
                 .abs()
-                .gt(lit(0.05)),
+                .gt(amount(transitory.tolerance)),
         )
         .collect()?;
-    Ok(if violations.shape().0 > 0 {
-        let row_vec = violations.get_row(0).unwrap().0;
-        let date = row_vec.first().unwrap().clone();
-        let net = row_vec.last().unwrap().clone();
-        Err(format!("Constraint violation for accounting export on {date}: net value of account {account} is {net} instead of 0.0").into())
+    if violations.shape().0 > 0 {
+        let violations = as_float(&violations)?;
+        let row_vec = violations.get_row(0)?.0;
+        let date = row_vec.first().ok_or("row without date")?.clone();
+        let net = row_vec.last().ok_or("row without net value")?.clone();
+        Err(format!(
+            "Constraint violation for accounting export on {date}: net value of account {} is {net} instead of 0.0",
+            transitory.account
+        )
+        .into())
     } else {
         Ok(())
-    })
+    }
+}
+
+/// the net value of the movements of the transitory account 10920 not posted in the banana export:
+/// the payments of sumup (debit minus credit)
+fn unposted_expr_10920() -> Expr {
+    -col("Payment SumUp")
 }
 
-fn calculate_correction_20051(
+/// calculates the posting `SU Rundungskorrektur <account>` netting the transitory account
+/// in the banana export against its counter account
+fn calculate_correction(
     df_acc: &DataFrame,
-    net_expr: &Expr,
+    df_banana: &DataFrame,
+    transitory: &TransitoryAccount,
     month: &str,
 ) -> Result<DataFrame, Box<dyn Error>> {
     let last_of_month = get_last_of_month_nd(month)?;
-    let correction = df_acc
+    let (account, counter_account) = (transitory.account, transitory.counter_account);
+    let posted = df_banana
+        .clone()
+        .lazy()
+        .select([(when(col("KtSoll").eq(lit(account)))
+            .then(col("Betrag CHF"))
+            .otherwise(zero())
+            - when(col("KtHaben").eq(lit(account)))
+                .then(col("Betrag CHF"))
+                .otherwise(zero()))
+        .sum()
+        .alias("Net")]);
+    let unposted = df_acc
         .clone()
         .lazy()
-        .with_column(
-            net_expr
-                .clone()
-                .round(2, RoundMode::HalfToEven)
-                .alias("Net"),
-        )
         .filter(col("Date").is_null())
+        .select([(transitory.unposted_expr)().sum().alias("Net")]);
+    let net = concat([posted, unposted], UnionArgs::default())?
+        .select([col("Net")
+            .sum()
+            .round(2, RoundMode::HalfToEven)
+            .alias("Net")])
+        .collect()?;
+    let excessive = net
+        .clone()
+        .lazy()
+        .filter(col("Net").abs().gt(amount(transitory.tolerance)))
+        .collect()?;
+    if excessive.height() > 0 {
+        let net = as_float(&excessive)?.column("Net")?.f64()?.get(0);
+        return Err(format!(
+            "Constraint violation for banana export: net value of account {account} is {} instead of 0.0",
+            net.unwrap_or_default()
+        )
+        .into());
+    }
+    // a debit surplus is booked on the credit side of the account and vice versa
+    let credit_account = when(col("Net").gt(zero()));
+    let correction = net
+        .lazy()
         .select([
             lit(last_of_month).cast(DataType::Date).alias("Datum"),
-            lit(NULL).alias("Beleg"),
-            lit(NULL).alias("Rechnung"),
-            lit("SU Rundungskorrektur 20051").alias("Beschreibung"),
-            when(col("Net").gt(0.0))
-                .then(lit("20051"))
-                .otherwise(lit("68450"))
+            lit("").alias("Beleg"),
+            lit("").alias("Rechnung"),
+            lit(format!("SU Rundungskorrektur {account}")).alias("Beschreibung"),
+            credit_account
+                .clone()
+                .then(lit(counter_account))
+                .otherwise(lit(account))
                 .alias("KtSoll"),
-            when(col("Net").lt(0.0))
-                .then(lit("20051"))
-                .otherwise(lit("68450"))
+            credit_account
+                .then(lit(account))
+                .otherwise(lit(counter_account))
                 .alias("KtHaben"),
-            lit(NULL).alias("Anzahl"),
-            lit(NULL).alias("Einheit"),
-            lit(NULL).alias("Preis/Einheit"),
+            lit("").alias("Anzahl"),
+            lit("").alias("Einheit"),
+            lit("").alias("Preis/Einheit"),
             col("Net").abs().alias("Betrag CHF"),
         ])
-        .filter(col("Betrag CHF").neq(lit(0.0)))
+        .filter(col("Betrag CHF").neq(zero()))
         .collect()?;
     Ok(correction)
 }
@@ -206,6 +296,8 @@ mod tests {
     use chrono::NaiveDate;
     use rstest::rstest;
 
+    use crate::export::export_banana::gather_df_banana;
+    use crate::money::as_money;
     use crate::test_fixtures::{
         accounting_df_03, accounting_df_06, accounting_df_08, details_df_03, details_df_06,
         details_df_08,
@@ -676,7 +768,6 @@ mod tests {
         #[case] account: &str,
     ) -> PolarsResult<()> {
         let date = NaiveDate::parse_from_str("17.4.23", "%d.%m.%y").expect("valid date");
-        let month = "202304";
         let df = df!(
             "Date" => &[date],
             "Payment SumUp" => &[payment_sumup],
@@ -708,14 +799,14 @@ mod tests {
             "20051/30500" => &[income_lola_miti],
             "10930/10100" => &[debt_to_miti],
         )?;
-        match validate_acc_constraint_and_calculate_correction_postings(&df, month) {
-            Ok(corr_df) => {
+        let df = as_money(&df)?;
+        match validate_acc_constraint(&df) {
+            Ok(()) => {
                 assert!(
                     delta.is_none(),
                     "Would not have expected delta {} on {date}.",
                     delta.unwrap()
                 );
-                assert_eq!(corr_df.shape().0, 0);
             }
             Err(e) => match delta {
                 Some(d) => assert_eq!(
@@ -829,7 +920,6 @@ mod tests {
         #[case] account: &str,
     ) -> PolarsResult<()> {
         let date = NaiveDate::parse_from_str("17.4.23", "%d.%m.%y").expect("valid date");
-        let month = "202304";
         let df = df!(
             "Date" => &[date],
             "Payment SumUp" => &[payment_sumup],
@@ -861,10 +951,9 @@ mod tests {
             "20051/30500" => &[income_lola_miti],
             "10930/10100" => &[debt_to_miti],
         )?;
-        match validate_acc_constraint_and_calculate_correction_postings(&df, month) {
-            Ok(corr_df) => {
-                assert_eq!(corr_df.shape().0, 0);
-            }
+        let df = as_money(&df)?;
+        match validate_acc_constraint(&df) {
+            Ok(()) => {}
             Err(e) => match delta {
                 Some(d) => assert_eq!(
                     e.to_string(),
@@ -878,4 +967,35 @@ mod tests {
 
         Ok(())
     }
+
+    #[rstest]
+    fn can_calculate_correction_postings(accounting_df_06: DataFrame) -> PolarsResult<()> {
+        let summary = accounting_df_06
+            .clone()
+            .lazy()
+            .sum()
+            .with_columns([
+                col("Payment SumUp") + amount("0.03"),
+                col("20121/10000") - amount("0.02"),
+            ])
+            .collect()?;
+        let mut df_acc = accounting_df_06;
+        df_acc.extend(&summary)?;
+        let expected = df!(
+            "Datum" => [NaiveDate::from_ymd_opt(2023, 3, 31); 2],
+            "Beschreibung" => ["SU Rundungskorrektur 10920", "SU Rundungskorrektur 20121"],
+            "KtSoll" => ["10920", "20121"],
+            "KtHaben" => ["68450", "68450"],
+            "Betrag CHF" => [0.03, 0.02],
+        )?;
+        let expected = as_money(&expected)?;
+
+        let df_banana = gather_df_banana(&df_acc, "202303")?;
+        let corrections = calculate_correction_postings(&df_acc, &df_banana, "202303")
+            .expect("differences are within tolerance");
+        let actual =
+            corrections.select(["Datum", "Beschreibung", "KtSoll", "KtHaben", "Betrag CHF"])?;
+        assert_dataframe(&actual, &expected);
+        Ok(())
+    }
 }
@@ -1,7 +1,10 @@
 use crate::export::get_last_of_month_nd;
+use crate::money::{MONEY, as_float, zero};
 use crate::prepare::{Owner, PaymentMethod, Topic};
 use polars::prelude::*;
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt::Write;
 
 use super::posting::Posting;
 
@@ -31,7 +34,8 @@ pub fn gather_df_banana(df_acct: &DataFrame, month: &str) -> PolarsResult<DataFr
         .clone()
         .lazy()
         .filter(col("Date").is_null())
-        .with_column(lit(0.0).alias("Date"))
+        // transposing decimals truncates them, hence the amounts are transposed as text
+        .select([all().as_expr().cast(DataType::String)])
         .collect()?
         .transpose(Some("KtSoll/KtHaben"), None)?
         .lazy()
@@ -54,6 +58,7 @@ pub fn gather_df_banana(df_acct: &DataFrame, month: &str) -> PolarsResult<DataFr
         .with_column(col("KtSoll/KtHaben").str().tail(lit(5)).alias("KtHaben"))
         .with_column(
             col("column_0")
+                .cast(MONEY)
                 .round(2, RoundMode::HalfToEven)
                 .alias("Betrag CHF"),
         )
@@ -111,9 +116,9 @@ pub fn gather_df_banana_details(raw_df: &DataFrame) -> PolarsResult<DataFrame> {
         .group_by(["Date", "Payment Method"])
         .agg([col("Price (Net)")
             .sum()
-            .fill_null(0.0)
+            .fill_null(zero())
             .alias("Betrag CHF")
-            .cast(DataType::Float64)])
+            .cast(MONEY)])
         .with_column(
             when(col("Payment Method").eq(lit(PaymentMethod::Card.to_string())))
                 .then(lit("10920"))
@@ -146,9 +151,9 @@ pub fn gather_df_banana_details(raw_df: &DataFrame) -> PolarsResult<DataFrame> {
         .group_by(["Date"])
         .agg([col("Price (Net)")
             .sum()
-            .fill_null(0.0)
+            .fill_null(zero())
             .alias("Betrag CHF")
-            .cast(DataType::Float64)])
+            .cast(MONEY)])
         .select([
             col("Date").alias("Datum"),
             lit("").alias("Beleg"),
@@ -167,11 +172,270 @@ pub fn gather_df_banana_details(raw_df: &DataFrame) -> PolarsResult<DataFrame> {
     rentals.clone().lazy().collect()
 }
 
+/// The clearing accounts of the payment instruments, keyed by the instrument (e.g. `TWINT = "10921"`)
+pub type ClearingAccounts = HashMap<String, String>;
+
+/// Gathers the transfers of the card payments from `10920` to the `clearing_accounts` of their payment instrument,
+/// one posting per instrument on the last of the `month`: the net price minus the commission,
+/// i.e. the amount paid out by sumup for the instrument.
+/// The card payments of instruments without clearing account remain on `10920`.
+/// Configured instruments that match no card payment instrument of the month (e.g. `Bar`) are rejected.
+pub fn gather_df_banana_clearing(
+    raw_df: &DataFrame,
+    month: &str,
+    clearing_accounts: &ClearingAccounts,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let last_of_month = get_last_of_month_nd(month)?;
+    let card_instruments = raw_df
+        .clone()
+        .lazy()
+        .filter(col("Payment Method").eq(lit(PaymentMethod::Card.to_string())))
+        .select([col("Payment Instrument").unique()])
+        .collect()?;
+    let card_instruments = card_instruments
+        .column("Payment Instrument")?
+        .str()?
+        .into_no_null_iter()
+        .collect::<Vec<_>>();
+    let mut unmatched = clearing_accounts
+        .keys()
+        .filter(|instrument| !card_instruments.contains(&instrument.as_str()))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !unmatched.is_empty() {
+        unmatched.sort_unstable();
+        return Err(format!(
+            "Clearing accounts configured for {} not matching any card payment instrument of {month}",
+            unmatched.join(", ")
+        )
+        .into());
+    }
+    let (instruments, accounts): (Vec<&str>, Vec<&str>) = clearing_accounts
+        .iter()
+        .map(|(instrument, account)| (instrument.as_str(), account.as_str()))
+        .unzip();
+    let routes = df!(
+        "Payment Instrument" => instruments,
+        "KtSoll" => accounts,
+    )?;
+    let transfers = raw_df
+        .clone()
+        .lazy()
+        .filter(col("Payment Method").eq(lit(PaymentMethod::Card.to_string())))
+        .join(
+            routes.lazy(),
+            [col("Payment Instrument")],
+            [col("Payment Instrument")],
+            JoinType::Inner.into(),
+        )
+        .group_by(["Payment Instrument", "KtSoll"])
+        .agg([
+            (col("Price (Net)").fill_null(zero()) - col("Commission").fill_null(zero()))
+                .sum()
+                .cast(MONEY)
+                .round(2, RoundMode::HalfToEven)
+                .alias("Betrag CHF"),
+        ])
+        .filter(col("Betrag CHF").neq(zero()))
+        .sort(["Payment Instrument"], SortMultipleOptions::default())
+        .select([
+            lit(last_of_month).cast(DataType::Date).alias("Datum"),
+            lit("").alias("Beleg"),
+            lit("").alias("Rechnung"),
+            concat_str([lit("SU Umbuchung"), col("Payment Instrument")], " ", true)
+                .alias("Beschreibung"),
+            col("KtSoll"),
+            lit("10920").alias("KtHaben"),
+            lit("").alias("Anzahl"),
+            lit("").alias("Einheit"),
+            lit("").alias("Preis/Einheit"),
+            col("Betrag CHF"),
+        ])
+        .collect()?;
+    Ok(transfers)
+}
+
+/// Fails if the `transfers` to the clearing accounts exceed the payments of sumup of the month
+/// (`Payment SumUp` of the summary row of the accounting export `df_acc`),
+/// as the transfers are derived from the card payments instead of the amounts posted to `10920`.
+pub fn validate_clearing_transfers(
+    transfers: &DataFrame,
+    df_acc: &DataFrame,
+) -> Result<(), Box<dyn Error>> {
+    let paid = df_acc
+        .clone()
+        .lazy()
+        .filter(col("Date").is_null())
+        .select([col("Payment SumUp").sum().alias("Paid")])
+        .collect()?;
+    let excess = transfers
+        .clone()
+        .lazy()
+        .select([col("Betrag CHF").sum().alias("Transferred")])
+        .with_column(paid.column("Paid")?.as_materialized_series().clone().lit())
+        .filter(col("Transferred").gt(col("Paid")))
+        .collect()?;
+    if excess.height() > 0 {
+        let excess = as_float(&excess)?;
+        let transferred = excess
+            .column("Transferred")?
+            .f64()?
+            .get(0)
+            .unwrap_or_default();
+        let paid = excess.column("Paid")?.f64()?.get(0).unwrap_or_default();
+        return Err(format!(
+            "Transfers to the clearing accounts of {transferred:.2} exceed the payments of sumup of {paid:.2}"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// How the postings of the banana export are numbered (column `Beleg`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum BelegNumbering {
+    /// `<prefix>-<yyyymm>-<nn>`, restarting with `01` every month
+    Monthly { prefix: String },
+    /// Plain numbers continuing from `start` (e.g. the next free number in Banana)
+    Continuing { start: u32 },
+}
+
+impl Default for BelegNumbering {
+    fn default() -> Self {
+        BelegNumbering::Monthly {
+            prefix: "SU".into(),
+        }
+    }
+}
+
+impl BelegNumbering {
+    /// The number of the posting at (zero-based) `index` of the `month`
+    fn beleg(&self, month: &str, index: u32) -> String {
+        match self {
+            BelegNumbering::Monthly { prefix } => format!("{prefix}-{month}-{:02}", index + 1),
+            BelegNumbering::Continuing { start } => (start + index).to_string(),
+        }
+    }
+}
+
+/// Numbers the postings of the banana export of the `month` in column `Beleg`.
+/// The postings are sorted by date (postings without date last), description, accounts and amount first,
+/// so the numbers are the same in every run on the same data.
+pub fn number_postings(
+    df_banana: &DataFrame,
+    month: &str,
+    numbering: &BelegNumbering,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let mut numbered = df_banana.sort(
+        ["Datum", "Beschreibung", "KtSoll", "KtHaben", "Betrag CHF"],
+        SortMultipleOptions::new()
+            .with_nulls_last(true)
+            .with_maintain_order(true),
+    )?;
+    let belege: Vec<String> = (0..u32::try_from(numbered.height())?)
+        .map(|index| numbering.beleg(month, index))
+        .collect();
+    numbered.with_column(Column::new("Beleg".into(), belege))?;
+    Ok(numbered)
+}
+
+/// The VAT codes of Banana, keyed by the account number
+pub type VatCodes = HashMap<String, String>;
+
+/// The columns of the banana export with the XML names of the corresponding columns in the Banana journal
+const BANANA_IMPORT_COLUMNS: [(&str, &str); 10] = [
+    ("Datum", "Date"),
+    ("Beleg", "Doc"),
+    ("Rechnung", "DocInvoice"),
+    ("Beschreibung", "Description"),
+    ("KtSoll", "AccountDebit"),
+    ("KtHaben", "AccountCredit"),
+    ("Anzahl", "Quantity"),
+    ("Einheit", "ReferenceUnit"),
+    ("Preis/Einheit", "UnitPrice"),
+    ("Betrag CHF", "Amount"),
+];
+
+/// Renders the banana export (`df_banana`) as tab-separated text file for Banana's "Import to accounting"
+/// (transactions): a header with the XML names of the columns followed by one line per posting,
+/// dates in ISO format and amounts with two decimals. Postings without date (to be dated when paid,
+/// e.g. `SU Zahlung an MiTi`) keep an empty date, as in the banana export.
+/// With `vat_codes`, the column `VatCode` holds the VAT code of the credit account, or else of the debit account.
+pub fn render_banana_import(
+    df_banana: &DataFrame,
+    vat_codes: Option<&VatCodes>,
+) -> Result<String, Box<dyn Error>> {
+    let columns: Vec<Expr> = BANANA_IMPORT_COLUMNS
+        .iter()
+        .map(|(name, xml_name)| match *name {
+            "Datum" => col(*name)
+                .dt()
+                .strftime("%Y-%m-%d")
+                .fill_null(lit(""))
+                .alias(*xml_name),
+            "Betrag CHF" => col(*name)
+                .cast(DataType::Float64)
+                .round(2, RoundMode::HalfToEven)
+                .alias(*xml_name),
+            _ => col(*name).cast(DataType::String).alias(*xml_name),
+        })
+        .collect();
+    let postings = df_banana.clone().lazy().select(columns).collect()?;
+
+    let mut import = postings.get_column_names_str().join("\t");
+    if vat_codes.is_some() {
+        import.push_str("\tVatCode");
+    }
+    import.push('\n');
+    let amounts = postings.column("Amount")?.f64()?;
+    let text_columns = postings
+        .get_columns()
+        .iter()
+        .filter(|c| c.name() != "Amount")
+        .map(|c| c.str())
+        .collect::<Result<Vec<_>, _>>()?;
+    for row in 0..postings.height() {
+        let fields: Vec<String> = text_columns
+            .iter()
+            .map(|c| {
+                c.get(row)
+                    .unwrap_or_default()
+                    .replace(['\t', '\n', '\r'], " ")
+                    .trim()
+                    .to_string()
+            })
+            .collect();
+        let (date, description) = (&fields[0], &fields[3]);
+        let amount = amounts
+            .get(row)
+            .ok_or_else(|| format!("Posting {date} {description} without amount"))?;
+        if fields[4].is_empty() && fields[5].is_empty() {
+            return Err(format!("Posting {date} {description} without account").into());
+        }
+        write!(import, "{}\t{amount:.2}", fields.join("\t"))?;
+        if let Some(vat_codes) = vat_codes {
+            let vat_code = vat_codes
+                .get(&fields[5])
+                .or_else(|| vat_codes.get(&fields[4]))
+                .map_or("", String::as_str);
+            write!(import, "\t{vat_code}")?;
+        }
+        import.push('\n');
+    }
+    Ok(import)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_fixtures::{accounting_df_06, accounting_df_08, banana_df_06, banana_df_08};
+    use crate::money::{amount, as_money};
+    use crate::test_fixtures::{
+        accounting_df_06, accounting_df_08, banana_df_06, banana_df_08, banana_df_ext_06,
+        intermediate_df_06, sample_date_lom,
+    };
     use crate::test_utils::assert_dataframe;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
     use rstest::rstest;
 
     #[rstest]
@@ -225,4 +489,151 @@ mod tests {
             gather_df_banana(&acct_df_ext, "202303").expect("should be able to collect banana_df");
         assert_dataframe(&out, &banana_df_08);
     }
+
+    #[rstest]
+    fn can_gather_transfers_to_clearing_accounts(
+        intermediate_df_06: DataFrame,
+        sample_date_lom: NaiveDate,
+    ) {
+        let clearing_accounts = ClearingAccounts::from([("TWINT".into(), "10921".into())]);
+        let out = gather_df_banana_clearing(&intermediate_df_06, "202303", &clearing_accounts)
+            .expect("should be able to gather transfers");
+        let expected = df!(
+            "Datum" => [sample_date_lom],
+            "Beleg" => [""],
+            "Rechnung" => [""],
+            "Beschreibung" => ["SU Umbuchung TWINT"],
+            "KtSoll" => ["10921"],
+            "KtHaben" => ["10920"],
+            "Anzahl" => [""],
+            "Einheit" => [""],
+            "Preis/Einheit" => [""],
+            "Betrag CHF" => [394.0],
+        )
+        .expect("valid transfer dataframe");
+        let expected = as_money(&expected).expect("valid amounts");
+        assert_dataframe(&out, &expected);
+    }
+
+    #[rstest]
+    fn given_unmatched_instruments_when_gathering_transfers_we_should_reject_them(
+        intermediate_df_06: DataFrame,
+    ) {
+        let clearing_accounts = ClearingAccounts::from([
+            ("TWINT".into(), "10921".into()),
+            ("Bar".into(), "10000".into()),
+            ("Amex".into(), "10923".into()),
+        ]);
+        let err = gather_df_banana_clearing(&intermediate_df_06, "202303", &clearing_accounts)
+            .expect_err("should reject instruments without card payments");
+        assert_eq!(
+            err.to_string(),
+            "Clearing accounts configured for Amex, Bar not matching any card payment instrument of 202303"
+        );
+    }
+
+    #[rstest]
+    fn given_transfers_exceeding_the_payments_when_validating_we_should_fail(
+        intermediate_df_06: DataFrame,
+        accounting_df_06: DataFrame,
+    ) {
+        let clearing_accounts = ClearingAccounts::from([("TWINT".into(), "10921".into())]);
+        let transfers =
+            gather_df_banana_clearing(&intermediate_df_06, "202303", &clearing_accounts)
+                .expect("should be able to gather transfers");
+        let summary = accounting_df_06
+            .clone()
+            .lazy()
+            .sum()
+            .collect()
+            .expect("can sum accounting_df_06");
+        let mut df_acc = accounting_df_06.clone();
+        df_acc.extend(&summary).expect("can add summary");
+        validate_clearing_transfers(&transfers, &df_acc).expect("transfers within payments");
+
+        let short = summary
+            .lazy()
+            .with_column(col("Payment SumUp") - amount("300"))
+            .collect()
+            .expect("can reduce payments");
+        let mut df_acc = accounting_df_06;
+        df_acc.extend(&short).expect("can add summary");
+        let err = validate_clearing_transfers(&transfers, &df_acc)
+            .expect_err("transfers exceed payments");
+        assert_eq!(
+            err.to_string(),
+            "Transfers to the clearing accounts of 394.00 exceed the payments of sumup of 291.00"
+        );
+    }
+
+    #[rstest]
+    fn given_no_clearing_accounts_when_gathering_transfers_we_should_gather_none(
+        intermediate_df_06: DataFrame,
+    ) {
+        let out =
+            gather_df_banana_clearing(&intermediate_df_06, "202303", &ClearingAccounts::new())
+                .expect("should be able to gather transfers");
+        assert_eq!(out.height(), 0);
+    }
+
+    #[rstest]
+    fn can_render_banana_import(banana_df_ext_06: DataFrame) {
+        let import = render_banana_import(&banana_df_ext_06, None).expect("can render import");
+        assert_eq!(
+            import,
+            "Date\tDoc\tDocInvoice\tDescription\tAccountDebit\tAccountCredit\tQuantity\tReferenceUnit\tUnitPrice\tAmount
+2023-03-24\t\t\tRental A\t10920\t31000\t\t\t\t400.00
+2023-03-24\t\t\tRental B\t10000\t31000\t\t\t\t600.00
+2023-03-25\t\t\tRental C\t10000\t31000\t\t\t\t100.00
+2023-03-25\t\t\tRental D\t10920\t31000\t\t\t\t200.00
+2023-03-31\t\t\tSU Kartenkommission\t68450\t10920\t\t\t\t9.00
+"
+        );
+    }
+
+    #[rstest]
+    fn can_render_banana_import_with_vat_codes() {
+        let postings = df!(
+            "Datum" => [None, NaiveDate::from_ymd_opt(2023, 3, 24)],
+            "Beleg" => ["SU-1", "SU-2"],
+            "Rechnung" => ["", ""],
+            "Beschreibung" => ["SU Zahlung an MiTi", "Rental\tA\n"],
+            "KtSoll" => ["20051", "68450"],
+            "KtHaben" => ["10920", "10000"],
+            "Anzahl" => ["", ""],
+            "Einheit" => ["", ""],
+            "Preis/Einheit" => ["", ""],
+            "Betrag CHF" => [120.5, 1.0 / 3.0],
+        )
+        .expect("valid banana dataframe");
+        let postings = as_money(&postings).expect("valid amounts");
+        let vat_codes = VatCodes::from([("68450".into(), "M81".into())]);
+        let import = render_banana_import(&postings, Some(&vat_codes)).expect("can render import");
+        assert_eq!(
+            import,
+            "Date\tDoc\tDocInvoice\tDescription\tAccountDebit\tAccountCredit\tQuantity\tReferenceUnit\tUnitPrice\tAmount\tVatCode
+\tSU-1\t\tSU Zahlung an MiTi\t20051\t10920\t\t\t\t120.50\t
+2023-03-24\tSU-2\t\tRental A\t68450\t10000\t\t\t\t0.33\tM81
+"
+        );
+    }
+
+    #[rstest]
+    #[case(BelegNumbering::default(), ["SU-202303-01", "SU-202303-02", "SU-202303-03", "SU-202303-04", "SU-202303-05"])]
+    #[case(BelegNumbering::Continuing { start: 118 }, ["118", "119", "120", "121", "122"])]
+    fn can_number_postings(
+        banana_df_ext_06: DataFrame,
+        #[case] numbering: BelegNumbering,
+        #[case] expected: [&str; 5],
+    ) {
+        let reversed = banana_df_ext_06.reverse();
+        let numbered =
+            number_postings(&reversed, "202303", &numbering).expect("can number postings");
+        let expected = banana_df_ext_06
+            .lazy()
+            .with_column(Series::new("Beleg".into(), expected).lit())
+            .collect()
+            .expect("valid numbered postings");
+        assert_dataframe(&numbered, &expected);
+    }
 }
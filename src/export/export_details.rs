@@ -1,6 +1,7 @@
 use polars::prelude::*;
 
 use crate::export::export_details::MitiMealType::{Children, Praktikum, Reduced, Regular};
+use crate::money::{MONEY, amount, zero};
 use crate::prepare::{Owner, PaymentMethod, Purpose, Topic};
 
 /// Produces the details dataframe from the `raw_df` read from the file
@@ -413,108 +414,109 @@ pub fn collect_data(raw_df: DataFrame) -> PolarsResult<DataFrame> {
     );
     with_meal_total_praktikum
         .with_column(
-            (col("MiTi_Cash").fill_null(0.0) + col("MiTi_Card").fill_null(0.0))
+            (col("MiTi_Cash").fill_null(zero()) + col("MiTi_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("MiTi Total"),
         )
         .with_column(
-            (col("Cafe_Cash").fill_null(0.0) + col("Cafe_Card").fill_null(0.0))
+            (col("Cafe_Cash").fill_null(zero()) + col("Cafe_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Cafe Total"),
         )
         .with_column(
-            (col("Verm_Cash").fill_null(0.0) + col("Verm_Card").fill_null(0.0))
+            (col("Verm_Cash").fill_null(zero()) + col("Verm_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Verm Total"),
         )
         .with_column(
-            (col("SoFe_Cash").fill_null(0.0) + col("SoFe_Card").fill_null(0.0))
+            (col("SoFe_Cash").fill_null(zero()) + col("SoFe_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("SoFe Total"),
         )
         .with_column(
-            (col("Deposit_Cash").fill_null(0.0) + col("Deposit_Card").fill_null(0.0))
+            (col("Deposit_Cash").fill_null(zero()) + col("Deposit_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Deposit Total"),
         )
         .with_column(
-            (col("Packaging_Cash").fill_null(0.0) + col("Packaging_Card").fill_null(0.0))
+            (col("Packaging_Cash").fill_null(zero()) + col("Packaging_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Packaging Total"),
         )
         .with_column(
-            (col("Rental_Cash").fill_null(0.0) + col("Rental_Card").fill_null(0.0))
+            (col("Rental_Cash").fill_null(zero()) + col("Rental_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Rental Total"),
         )
         .with_column(
-            (col("Culture_Cash").fill_null(0.0) + col("Culture_Card").fill_null(0.0))
+            (col("Culture_Cash").fill_null(zero()) + col("Culture_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Culture Total"),
         )
         .with_column(
-            (col("Culture (LoLa) Cash").fill_null(0.0) + col("Culture (LoLa) Card").fill_null(0.0))
-                .round(2, RoundMode::HalfToEven)
-                .alias("Culture (LoLa) Total"),
+            (col("Culture (LoLa) Cash").fill_null(zero())
+                + col("Culture (LoLa) Card").fill_null(zero()))
+            .round(2, RoundMode::HalfToEven)
+            .alias("Culture (LoLa) Total"),
         )
         .with_column(
-            (col("Culture (PaidOut) Cash").fill_null(0.0)
-                + col("Culture (PaidOut) Card").fill_null(0.0))
+            (col("Culture (PaidOut) Cash").fill_null(zero())
+                + col("Culture (PaidOut) Card").fill_null(zero()))
             .round(2, RoundMode::HalfToEven)
             .alias("Culture (PaidOut) Total"),
         )
         .with_column(
-            (col("MiTi_Cash").fill_null(0.0)
-                + col("Cafe_Cash").fill_null(0.0)
-                + col("Verm_Cash").fill_null(0.0)
-                + col("SoFe_Cash").fill_null(0.0)
-                + col("Deposit_Cash").fill_null(0.0)
-                + col("Packaging_Cash").fill_null(0.0)
-                + col("Rental_Cash").fill_null(0.0)
-                + col("Culture_Cash").fill_null(0.0))
+            (col("MiTi_Cash").fill_null(zero())
+                + col("Cafe_Cash").fill_null(zero())
+                + col("Verm_Cash").fill_null(zero())
+                + col("SoFe_Cash").fill_null(zero())
+                + col("Deposit_Cash").fill_null(zero())
+                + col("Packaging_Cash").fill_null(zero())
+                + col("Rental_Cash").fill_null(zero())
+                + col("Culture_Cash").fill_null(zero()))
             .round(2, RoundMode::HalfToEven)
             .alias("Gross Cash"),
         )
         .with_column(
-            (col("MiTi_Card").fill_null(0.0)
-                + col("Cafe_Card").fill_null(0.0)
-                + col("Verm_Card").fill_null(0.0)
-                + col("SoFe_Card").fill_null(0.0)
-                + col("Deposit_Card").fill_null(0.0)
-                + col("Packaging_Card").fill_null(0.0)
-                + col("Rental_Card").fill_null(0.0)
-                + col("Culture_Card").fill_null(0.0))
+            (col("MiTi_Card").fill_null(zero())
+                + col("Cafe_Card").fill_null(zero())
+                + col("Verm_Card").fill_null(zero())
+                + col("SoFe_Card").fill_null(zero())
+                + col("Deposit_Card").fill_null(zero())
+                + col("Packaging_Card").fill_null(zero())
+                + col("Rental_Card").fill_null(zero())
+                + col("Culture_Card").fill_null(zero()))
             .round(2, RoundMode::HalfToEven)
             .alias("Gross Card"),
         )
         .with_column(
             col("MiTi_Card")
-                .fill_null(0.0)
+                .fill_null(zero())
                 .round(2, RoundMode::HalfToEven)
                 .alias("Gross Card MiTi"),
         )
         .with_column(
-            (col("Gross Card").fill_null(0.0) - col("MiTi_Card").fill_null(0.0))
+            (col("Gross Card").fill_null(zero()) - col("MiTi_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Gross Card LoLa"),
         )
         .with_column(
-            (col("Gross Cash").fill_null(0.0) + col("Gross Card").fill_null(0.0))
+            (col("Gross Cash").fill_null(zero()) + col("Gross Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Gross Total"),
         )
         .with_column(
-            (col("Tips_Cash").fill_null(0.0) + col("Tips_Card").fill_null(0.0))
+            (col("Tips_Cash").fill_null(zero()) + col("Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Tips Total"),
         )
         .with_column(
-            (col("Gross Cash").fill_null(0.0) + col("Tips_Cash").fill_null(0.0))
+            (col("Gross Cash").fill_null(zero()) + col("Tips_Cash").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("SumUp Cash"),
         )
         .with_column(
-            (col("Gross Card").fill_null(0.0) + col("Tips_Card").fill_null(0.0))
+            (col("Gross Card").fill_null(zero()) + col("Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("SumUp Card"),
         )
@@ -524,58 +526,61 @@ pub fn collect_data(raw_df: DataFrame) -> PolarsResult<DataFrame> {
                 .alias("SumUp Total"),
         )
         .with_column(
-            (col("MiTi_Commission").fill_null(0.0) + col("LoLa_Commission").fill_null(0.0))
+            (col("MiTi_Commission").fill_null(zero()) + col("LoLa_Commission").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Total Commission"),
         )
         .with_column(
-            (col("Gross Card MiTi").fill_null(0.0) - col("MiTi_Commission").fill_null(0.0))
+            (col("Gross Card MiTi").fill_null(zero()) - col("MiTi_Commission").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Net Card MiTi"),
         )
         .with_column(
-            (col("Gross Card LoLa").fill_null(0.0) - col("LoLa_Commission").fill_null(0.0))
+            (col("Gross Card LoLa").fill_null(zero()) - col("LoLa_Commission").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Net Card LoLa"),
         )
         .with_column(
-            (col("Gross Card").fill_null(0.0) - col("Total Commission").fill_null(0.0))
+            (col("Gross Card").fill_null(zero()) - col("Total Commission").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Net Card Total"),
         )
         .with_column(
-            (col("MiTi_Card").fill_null(0.0) + col("MiTi_Tips_Card").fill_null(0.0)
-                - col("MiTi_Total_Commission").fill_null(0.0))
+            (col("MiTi_Card").fill_null(zero()) + col("MiTi_Tips_Card").fill_null(zero())
+                - col("MiTi_Total_Commission").fill_null(zero()))
             .round(2, RoundMode::HalfToEven)
             .alias("Net Payment SumUp MiTi"),
         )
         .with_column(
-            (col("Gross MiTi (MiTi) Card").fill_null(0.0) - col("MiTi_Commission").fill_null(0.0))
-                .round(2, RoundMode::HalfToEven)
-                .alias("Net MiTi (MiTi) Card"),
+            (col("Gross MiTi (MiTi) Card").fill_null(zero())
+                - col("MiTi_Commission").fill_null(zero()))
+            .round(2, RoundMode::HalfToEven)
+            .alias("Net MiTi (MiTi) Card"),
         )
         .with_column(
-            (col("Gross MiTi (LoLa)").fill_null(0.0) - col("LoLa_Commission_MiTi").fill_null(0.0))
-                .round(2, RoundMode::HalfToEven)
-                .alias("Net MiTi (LoLa)"),
+            (col("Gross MiTi (LoLa)").fill_null(zero())
+                - col("LoLa_Commission_MiTi").fill_null(zero()))
+            .round(2, RoundMode::HalfToEven)
+            .alias("Net MiTi (LoLa)"),
         )
         .with_column(
-            (lit(0.2) * col("Net MiTi (LoLa)").fill_null(0.0))
+            (amount("0.2") * col("Net MiTi (LoLa)").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Contribution MiTi"),
         )
         .with_column(
-            (col("Gross MiTi (LoLa)").fill_null(0.0) - col("Contribution MiTi").fill_null(0.0))
-                .round(2, RoundMode::HalfToEven)
-                .alias("Income LoLa MiTi"),
+            (col("Gross MiTi (LoLa)").fill_null(zero())
+                - col("Contribution MiTi").fill_null(zero()))
+            .round(2, RoundMode::HalfToEven)
+            .alias("Income LoLa MiTi"),
         )
         .with_column(
-            (col("Net MiTi (LoLa)") * lit(0.8))
+            (col("Net MiTi (LoLa)") * amount("0.8"))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Net MiTi (LoLA) - Share LoLa"),
         )
         .with_column(
-            (lit(2.0)
+            (amount("2")
                 * (col("MealCount_Reduced").fill_null(0)
                     + col("MealCount_Praktikum").fill_null(0)))
             .round(2, RoundMode::HalfToEven)
@@ -588,7 +593,7 @@ pub fn collect_data(raw_df: DataFrame) -> PolarsResult<DataFrame> {
             .alias("Debt to MiTi"),
         )
         .with_column(
-            (col("Net Card Total") + col("Tips_Card").fill_null(0.0))
+            (col("Net Card Total") + col("Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("PaidOut Sumup"),
         )
@@ -700,10 +705,10 @@ fn key_figure_by_date_for(
         .group_by(["Date"])
         .agg([col(key_figure)
             .sum()
-            .fill_null(0.0)
+            .fill_null(zero())
             .round(2, RoundMode::HalfToEven)
             .alias(alias.as_str())
-            .cast(DataType::Float64)])
+            .cast(MONEY)])
         .sort(
             ["Date"],
             SortMultipleOptions::new()
@@ -821,7 +826,7 @@ fn meal_total(predicate_and_alias: (Expr, String), ldf: LazyFrame) -> LazyFrame
             .fill_null(0)
             .sum()
             .alias("Total Praktikum")
-            .cast(DataType::Float64)])
+            .cast(MONEY)])
         .sort(
             ["Date"],
             SortMultipleOptions::new()
@@ -930,6 +935,7 @@ impl FilterExpressionProvider for MitiMealType {
 mod tests {
     use rstest::rstest;
 
+    use crate::money::as_money;
     use crate::test_fixtures::{
         details_df_02, details_df_06, details_df_08, intermediate_df_02, intermediate_df_06,
         intermediate_df_08,
@@ -1022,9 +1028,12 @@ mod tests {
             Purpose::Consumption => consumption_of(&topic, &payment_method),
             Purpose::Tip => tips_of_topic(&topic),
         };
-        let out = price_by_date_for(paa, df_in.lazy()).collect()?;
+        let out = price_by_date_for(paa, as_money(&df_in)?.lazy()).collect()?;
 
-        assert_dataframe(&out, &expected.expect("Misconfigured expected dataframe"));
+        assert_dataframe(
+            &out,
+            &as_money(&expected.expect("Misconfigured expected dataframe"))?,
+        );
 
         Ok(())
     }
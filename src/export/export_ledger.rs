@@ -0,0 +1,239 @@
+use crate::export::get_last_of_month_nd;
+use polars::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt::Write;
+
+/// The names of the accounts in the plain-text journals, keyed by the account number
+pub type AccountNames = HashMap<String, String>;
+
+/// The plain-text accounting formats the banana postings are rendered in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlainTextFormat {
+    /// Journal readable by ledger and hledger
+    Ledger,
+    /// Beancount input file
+    Beancount,
+}
+
+impl PlainTextFormat {
+    /// The prefix and the extension of the exported file
+    pub fn file_name_parts(self) -> (&'static str, &'static str) {
+        match self {
+            PlainTextFormat::Ledger => ("ledger", "journal"),
+            PlainTextFormat::Beancount => ("beancount", "beancount"),
+        }
+    }
+}
+
+/// The name of the account as configured in `names`, or the account number below the root account
+/// of its class (e.g. `Assets:10920` or `Income:31000`) if not configured.
+fn account_name(account: &str, names: &AccountNames) -> String {
+    if let Some(name) = names.get(account) {
+        return name.clone();
+    }
+    let root = match account.chars().next() {
+        Some('1') => "Assets",
+        Some('2') if account.starts_with("28") => "Equity",
+        Some('2') => "Liabilities",
+        Some('3') => "Income",
+        Some('4'..='8') => "Expenses",
+        _ => "Equity",
+    };
+    format!("{root}:{account}")
+}
+
+/// Renders the postings of the banana export (`df_banana`) as plain-text journal in the given `format`,
+/// one transaction per posting with the debit account (`KtSoll`) receiving the amount
+/// and the credit account (`KtHaben`) giving it, referring to the number of the posting (`Beleg`) if any.
+/// Postings without date (to be dated when paid) are dated on the last of the `month` and flagged as pending.
+/// The beancount file starts with the operating currency and opens the accounts posted to
+/// on the date of the first posting.
+pub fn render_plain_text(
+    df_banana: &DataFrame,
+    names: &AccountNames,
+    month: &str,
+    format: PlainTextFormat,
+) -> Result<String, Box<dyn Error>> {
+    let last_of_month = get_last_of_month_nd(month)?.format("%Y-%m-%d").to_string();
+    let postings = df_banana
+        .clone()
+        .lazy()
+        .select([
+            col("Datum").is_null().alias("Pending"),
+            col("Datum")
+                .dt()
+                .strftime("%Y-%m-%d")
+                .fill_null(lit(last_of_month))
+                .alias("Date"),
+            col("Beleg").fill_null(lit("")),
+            col("Beschreibung").fill_null(lit("")),
+            col("KtSoll"),
+            col("KtHaben"),
+            col("Betrag CHF").cast(DataType::Float64),
+        ])
+        .collect()?;
+
+    let mut journal = String::new();
+    let mut accounts = BTreeSet::new();
+    for ((((((pending, date), beleg), description), debit), credit), amount) in postings
+        .column("Pending")?
+        .bool()?
+        .into_no_null_iter()
+        .zip(postings.column("Date")?.str()?.into_no_null_iter())
+        .zip(postings.column("Beleg")?.str()?.into_no_null_iter())
+        .zip(postings.column("Beschreibung")?.str()?.into_no_null_iter())
+        .zip(postings.column("KtSoll")?.str()?)
+        .zip(postings.column("KtHaben")?.str()?)
+        .zip(postings.column("Betrag CHF")?.f64()?)
+    {
+        let (Some(debit), Some(credit), Some(amount)) = (debit, credit, amount) else {
+            return Err(format!("Incomplete posting {date} {description}").into());
+        };
+        let flag = if pending { '!' } else { '*' };
+        let description = description.replace(['\n', '\r'], " ");
+        match format {
+            PlainTextFormat::Ledger if beleg.is_empty() => {
+                writeln!(journal, "{date} {flag} {description}")?;
+            }
+            PlainTextFormat::Ledger => writeln!(journal, "{date} {flag} ({beleg}) {description}")?,
+            PlainTextFormat::Beancount => {
+                writeln!(journal, "{date} {flag} \"{}\"", escape(&description))?;
+                if !beleg.is_empty() {
+                    writeln!(journal, "    beleg: \"{}\"", escape(beleg))?;
+                }
+            }
+        }
+        for (account, amount) in [(debit, amount), (credit, -amount)] {
+            let account = account_name(account, names);
+            accounts.insert(account.clone());
+            match format {
+                PlainTextFormat::Ledger => writeln!(journal, "    {account:<40}  CHF {amount:.2}")?,
+                PlainTextFormat::Beancount => {
+                    writeln!(journal, "    {account:<40}  {amount:.2} CHF")?;
+                }
+            }
+        }
+        journal.push('\n');
+    }
+    if format == PlainTextFormat::Beancount {
+        journal = beancount_header(&postings, &accounts)? + &journal;
+    }
+    Ok(journal)
+}
+
+/// The operating currency and the `open` directives of the `accounts`,
+/// dated on the date of the first of the `postings`
+fn beancount_header(
+    postings: &DataFrame,
+    accounts: &BTreeSet<String>,
+) -> Result<String, Box<dyn Error>> {
+    let mut header = String::from("option \"operating_currency\" \"CHF\"\n\n");
+    let Some(first_date) = postings.column("Date")?.str()?.into_no_null_iter().min() else {
+        return Ok(header);
+    };
+    for account in accounts {
+        writeln!(header, "{first_date} open {account} CHF")?;
+    }
+    header.push('\n');
+    Ok(header)
+}
+
+/// Escapes backslashes and double quotes in a beancount string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::as_money;
+    use crate::test_fixtures::banana_df_06;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("10920", "Assets:10920")]
+    #[case("20121", "Liabilities:20121")]
+    #[case("28000", "Equity:28000")]
+    #[case("31000", "Income:31000")]
+    #[case("68450", "Expenses:68450")]
+    #[case("10000", "Assets:Kasse")]
+    fn can_name_accounts(#[case] account: &str, #[case] expected: &str) {
+        let names = AccountNames::from([("10000".into(), "Assets:Kasse".into())]);
+        assert_eq!(account_name(account, &names), expected);
+    }
+
+    #[rstest]
+    fn can_render_ledger(banana_df_06: DataFrame) {
+        let names = AccountNames::from([("10000".into(), "Assets:Kasse".into())]);
+        let mut postings = banana_df_06;
+        postings
+            .with_column(Column::new("Beleg".into(), ["118", "", "120"]))
+            .expect("can number postings");
+        let journal = render_plain_text(&postings, &names, "202303", PlainTextFormat::Ledger)
+            .expect("can render ledger journal");
+        assert_eq!(
+            journal,
+            "2023-03-31 * (118) SU Vermietungen bar
+    Assets:Kasse                              CHF 700.00
+    Income:31000                              CHF -700.00
+
+2023-03-31 * SU Vermietungen Karte
+    Assets:10920                              CHF 600.00
+    Income:31000                              CHF -600.00
+
+2023-03-31 * (120) SU Kartenkommission
+    Expenses:68450                            CHF 9.00
+    Assets:10920                              CHF -9.00
+
+"
+        );
+    }
+
+    #[rstest]
+    fn can_render_beancount() {
+        let postings = df!(
+            "Datum" => [None, NaiveDate::from_ymd_opt(2023, 3, 24)],
+            "Beleg" => ["SU-202303-02", ""],
+            "Rechnung" => ["", ""],
+            "Beschreibung" => ["SU Zahlung an MiTi", "Rental \"A\""],
+            "KtSoll" => ["20051", "10000"],
+            "KtHaben" => ["10920", "31000"],
+            "Anzahl" => ["", ""],
+            "Einheit" => ["", ""],
+            "Preis/Einheit" => ["", ""],
+            "Betrag CHF" => [120.5, 400.0],
+        )
+        .expect("valid banana dataframe");
+        let postings = as_money(&postings).expect("valid amounts");
+        let journal = render_plain_text(
+            &postings,
+            &AccountNames::new(),
+            "202303",
+            PlainTextFormat::Beancount,
+        )
+        .expect("can render beancount");
+        assert_eq!(
+            journal,
+            "option \"operating_currency\" \"CHF\"
+
+2023-03-24 open Assets:10000 CHF
+2023-03-24 open Assets:10920 CHF
+2023-03-24 open Income:31000 CHF
+2023-03-24 open Liabilities:20051 CHF
+
+2023-03-31 ! \"SU Zahlung an MiTi\"
+    beleg: \"SU-202303-02\"
+    Liabilities:20051                         120.50 CHF
+    Assets:10920                              -120.50 CHF
+
+2023-03-24 * \"Rental \\\"A\\\"\"
+    Assets:10000                              400.00 CHF
+    Income:31000                              -400.00 CHF
+
+"
+        );
+    }
+}
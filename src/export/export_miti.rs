@@ -1,5 +1,7 @@
 use polars::prelude::*;
 
+use crate::money::{amount, zero};
+
 /// Produces the Mittagstisch dataframe from the details [df]
 pub fn gather_df_miti(df_det: &DataFrame) -> PolarsResult<DataFrame> {
     df_det
@@ -10,42 +12,43 @@ pub fn gather_df_miti(df_det: &DataFrame) -> PolarsResult<DataFrame> {
                 .alias("MealCount_ReducedPraktikum"),
         )
         .with_column(
-            (col("MiTi_Cash").fill_null(0.0) + col("MiTi_Tips_Cash").fill_null(0.0))
+            (col("MiTi_Cash").fill_null(zero()) + col("MiTi_Tips_Cash").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Total Cash"),
         )
         .with_column(
-            (col("MiTi_Card").fill_null(0.0) + col("MiTi_Tips_Card").fill_null(0.0))
+            (col("MiTi_Card").fill_null(zero()) + col("MiTi_Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Total Card"),
         )
         .with_column(
-            (col("MiTi_Tips_Cash").fill_null(0.0) + col("MiTi_Tips_Card").fill_null(0.0))
+            (col("MiTi_Tips_Cash").fill_null(zero()) + col("MiTi_Tips_Card").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Tips Total"),
         )
         .with_column(
-            (col("MiTi Total").fill_null(0.0) + col("Tips Total").fill_null(0.0))
+            (col("MiTi Total").fill_null(zero()) + col("Tips Total").fill_null(zero()))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Payment Total"),
         )
         .with_column(
-            (col("Gross MiTi (LoLa)").fill_null(0.0) - col("LoLa_Commission_MiTi").fill_null(0.0))
-                .round(2, RoundMode::HalfToEven)
-                .alias("Net Income LoLa"),
+            (col("Gross MiTi (LoLa)").fill_null(zero())
+                - col("LoLa_Commission_MiTi").fill_null(zero()))
+            .round(2, RoundMode::HalfToEven)
+            .alias("Net Income LoLa"),
         )
         .with_column(
-            (col("Gross MiTi (LoLa)").fill_null(0.0) * lit(0.8))
+            (col("Gross MiTi (LoLa)").fill_null(zero()) * amount("0.8"))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Gross MiTi LoLa (LoLa)"),
         )
         .with_column(
-            (col("Gross MiTi (LoLa)").fill_null(0.0) * lit(0.2))
+            (col("Gross MiTi (LoLa)").fill_null(zero()) * amount("0.2"))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Gross MiTi LoLa (MiTi)"),
         )
         .with_column(
-            (col("Net MiTi (LoLA) - Share LoLa").fill_null(0.0) * lit(-1))
+            (col("Net MiTi (LoLA) - Share LoLa").fill_null(zero()) * lit(-1))
                 .round(2, RoundMode::HalfToEven)
                 .alias("Verkauf LoLa (80%)"),
         )
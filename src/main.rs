@@ -7,12 +7,13 @@ use std::path::PathBuf;
 use chrono::Local;
 use clap::{Parser, Subcommand};
 
-use crate::close::close;
-use crate::export::export;
+use crate::close::{PeriodOptions, check_budget, close, init_budget, propose_budget};
+use crate::export::{BelegNumbering, ExportOptions, export};
 use crate::prepare::prepare;
 
 mod close;
 mod export;
+mod money;
 mod prepare;
 
 #[cfg(test)]
@@ -48,6 +49,24 @@ enum Commands {
     Export {
         /// the intermediate file to process
         intermediate_file: PathBuf,
+        /// write the banana postings as plain-text journals for ledger/hledger and beancount
+        #[arg(long)]
+        plain_text: bool,
+        /// the names of the accounts in the plain-text journals in TOML format (e.g. `10000 = "Assets:Kasse"`)
+        #[arg(short, long, requires = "plain_text")]
+        account_names: Option<PathBuf>,
+        /// the VAT codes of the accounts in the banana import file in TOML format (e.g. `31000 = "V77"`)
+        #[arg(long)]
+        vat_codes: Option<PathBuf>,
+        /// the clearing accounts of payment instruments not to be cleared via 10920 in TOML format (e.g. `TWINT = "10921"`)
+        #[arg(long)]
+        clearing_accounts: Option<PathBuf>,
+        /// the prefix of the numbers of the banana postings (`<prefix>-<yyyymm>-<nn>`)
+        #[arg(long, default_value = "SU")]
+        beleg_prefix: String,
+        /// the number of the first banana posting, numbering the postings continuously instead of per month
+        #[arg(long, conflicts_with = "beleg_prefix")]
+        beleg_start: Option<u32>,
     },
     /// Run the monthly closing process
     Close {
@@ -55,6 +74,50 @@ enum Commands {
         budget_config_file: PathBuf,
         /// The spreadsheet export file from the accounting software
         accounts_file: PathBuf,
+        /// The spreadsheet export file of the previous year for comparison
+        #[arg(short, long)]
+        previous_accounts_file: Option<PathBuf>,
+        /// The first month to report (`<yyyymm>`), defaults to the start of the fiscal year
+        #[arg(long, value_parser = month_in_range)]
+        from: Option<String>,
+        /// The last month to report (`<yyyymm>`), defaults to the month of the accounts file
+        #[arg(long, value_parser = month_in_range)]
+        to: Option<String>,
+        /// The quarter of the fiscal year to report (`<yyyy>Q<n>`, e.g. 2025Q2), the fiscal year starting in `<yyyy>`
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        quarter: Option<String>,
+        /// The budget scenario to compare with (e.g. `revised`), defaults to the amounts of the year
+        #[arg(long)]
+        scenario: Option<String>,
+    },
+    /// Maintain the budget configuration
+    Budget {
+        #[command(subcommand)]
+        command: BudgetCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum BudgetCommands {
+    /// Checks the budget configuration for inconsistencies
+    Check {
+        /// the budget configuration file in TOML format
+        budget_config_file: PathBuf,
+    },
+    /// Creates a budget configuration skeleton for the following year from an accounts file
+    Init {
+        /// The spreadsheet export file from the accounting software
+        accounts_file: PathBuf,
+    },
+    /// Proposes the budget of the following year from the current budget and an accounts file
+    Propose {
+        /// the budget configuration file in TOML format
+        budget_config_file: PathBuf,
+        /// The spreadsheet export file from the accounting software
+        accounts_file: PathBuf,
+        /// the adjustments (base, growth in percent, fixed amounts per post) in TOML format
+        #[arg(short, long)]
+        adjustments: Option<PathBuf>,
     },
 }
 
@@ -73,15 +136,65 @@ fn main() -> Result<(), Box<dyn Error>> {
             &intermediate_file(month, ts),
             month,
         ),
-        Commands::Export { intermediate_file } => {
+        Commands::Export {
+            intermediate_file,
+            plain_text,
+            account_names,
+            vat_codes,
+            clearing_accounts,
+            beleg_prefix,
+            beleg_start,
+        } => {
             let file_name = intermediate_file.as_os_str().to_str();
             let month = derive_month_from_intermediate(file_name)?;
-            export(intermediate_file, &month, ts)
+            let options = ExportOptions {
+                plain_text: *plain_text,
+                account_names: account_names.clone(),
+                vat_codes: vat_codes.clone(),
+                clearing_accounts: clearing_accounts.clone(),
+                numbering: match beleg_start {
+                    Some(start) => BelegNumbering::Continuing { start: *start },
+                    None => BelegNumbering::Monthly {
+                        prefix: beleg_prefix.clone(),
+                    },
+                },
+            };
+            export(intermediate_file, &month, &options, ts)
         }
         Commands::Close {
             budget_config_file,
             accounts_file,
-        } => close(budget_config_file, accounts_file, ts),
+            previous_accounts_file,
+            from,
+            to,
+            quarter,
+            scenario,
+        } => close(
+            budget_config_file,
+            accounts_file,
+            previous_accounts_file.as_deref(),
+            &PeriodOptions {
+                from: from.clone(),
+                to: to.clone(),
+                quarter: quarter.clone(),
+            },
+            scenario.as_deref(),
+            ts,
+        ),
+        Commands::Budget { command } => match command {
+            BudgetCommands::Check { budget_config_file } => check_budget(budget_config_file),
+            BudgetCommands::Init { accounts_file } => init_budget(accounts_file, ts),
+            BudgetCommands::Propose {
+                budget_config_file,
+                accounts_file,
+                adjustments,
+            } => propose_budget(
+                budget_config_file,
+                accounts_file,
+                adjustments.as_deref(),
+                ts,
+            ),
+        },
     }
 }
 
@@ -0,0 +1,87 @@
+use polars::prelude::*;
+
+/// The data type of all amounts: a decimal with four fractional digits,
+/// exact for the amounts of sumup (two decimals) as well as for the shares derived from them (e.g. 20%).
+pub const MONEY: DataType = DataType::Decimal(38, 4);
+
+/// The amount zero, e.g. to fill missing amounts
+/// (a float literal would turn the amounts into floating point numbers)
+pub fn zero() -> Expr {
+    lit(0).cast(MONEY)
+}
+
+/// An exact amount from its decimal representation, e.g. `amount("0.2")`
+pub fn amount(value: &str) -> Expr {
+    lit(value).cast(MONEY)
+}
+
+/// Casts the floating point columns of `df` to [`MONEY`].
+/// The values are rounded to four decimals, i.e. amounts parsed with at most four decimals are exact.
+pub fn as_money(df: &DataFrame) -> PolarsResult<DataFrame> {
+    cast_columns(df, &DataType::Float64, &MONEY)
+}
+
+/// Casts the [`MONEY`] columns of `df` to floating point numbers, e.g. for writing them to excel
+pub fn as_float(df: &DataFrame) -> PolarsResult<DataFrame> {
+    cast_columns(df, &MONEY, &DataType::Float64)
+}
+
+fn cast_columns(df: &DataFrame, from: &DataType, to: &DataType) -> PolarsResult<DataFrame> {
+    let casts: Vec<Expr> = df
+        .get_columns()
+        .iter()
+        .filter(|c| c.dtype() == from)
+        .map(|c| col(c.name().clone()).cast(to.clone()))
+        .collect();
+    df.clone().lazy().with_columns(casts).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn can_sum_amounts_exactly() {
+        let df = as_money(&df!("Amount" => [0.1, 0.2, -0.3]).expect("valid dataframe"))
+            .expect("can cast to money");
+        let sum = df
+            .lazy()
+            .select([col("Amount").sum().eq(zero()).alias("Zero")])
+            .collect()
+            .expect("can sum amounts");
+        assert_eq!(
+            sum.column("Zero")
+                .expect("zero")
+                .bool()
+                .expect("bool")
+                .get(0),
+            Some(true)
+        );
+    }
+
+    #[rstest]
+    fn can_cast_amounts_back_to_float() {
+        let df = df!("Amount" => ["1234.565", "0.1"], "Count" => [1, 2])
+            .expect("valid dataframe")
+            .lazy()
+            .with_column(col("Amount").cast(MONEY))
+            .collect()
+            .expect("can cast to money");
+        let floats = as_float(&df).expect("can cast to float");
+        assert_eq!(
+            floats
+                .column("Amount")
+                .expect("amount")
+                .f64()
+                .expect("float")
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec![1234.565, 0.1]
+        );
+        assert_eq!(
+            floats.column("Count").expect("count").dtype(),
+            &DataType::Int32
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::path::Path;
@@ -11,6 +13,8 @@ use polars_excel_writer::PolarsExcelWriter;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter, EnumString};
 
+use crate::money::{MONEY, as_float, as_money, zero};
+
 /// Processes the sumup input files (sales-report and transaction report) to produce an intermediate file.
 /// Some derived fields are prepared based on heuristics in a best-effort approach (Topic, Owner, Purpose).
 /// The user may optionally redact those where the heuristics are not sufficient.
@@ -33,7 +37,7 @@ pub fn prepare(
     excel_writer.set_column_format("Date", "dd.mm.YYYY");
     excel_writer.set_dtype_float_format("#,##0.00");
     excel_writer.set_freeze_panes(1, 2);
-    excel_writer.write_dataframe(&df)?;
+    excel_writer.write_dataframe(&as_float(&df)?)?;
     excel_writer.save(output_path)?;
     Ok(())
 }
@@ -44,54 +48,58 @@ fn process_input(
     transaction_report: &Path,
 ) -> Result<DataFrame, Box<dyn Error>> {
     let parse_options = CsvParseOptions::default().with_separator(b',');
-    let sr_df = CsvReadOptions::default()
-        .with_has_header(true)
-        .with_infer_schema_length(Some(1500))
-        .with_parse_options(parse_options.clone())
-        .try_into_reader_with_file_path(Some(sales_report.into()))?
-        .finish()?
-        .lazy()
-        .with_column(
-            col("Preis (netto)")
-                .fill_null(col("Preis (brutto)"))
-                .alias("Preis (netto)"),
-        )
-        .with_column(
-            when(
-                col("Beschreibung")
-                    .eq(lit("SCHICHTWECHSEL"))
-                    .or(col("Beschreibung").eq(lit("Mittagstisch-Nachmittag")))
-                    .or(col("Beschreibung").eq(lit("Nachmittag-Abend"))),
-            )
-            .then(0.0)
-            .otherwise(col("Preis (brutto)"))
-            .alias("Preis (brutto)"),
-        )
-        .with_column(
-            when(
-                col("Beschreibung")
-                    .eq(lit("SCHICHTWECHSEL"))
-                    .or(col("Beschreibung").eq(lit("Mittagstisch-Nachmittag")))
-                    .or(col("Beschreibung").eq(lit("Nachmittag-Abend"))),
-            )
-            .then(0.0)
-            .otherwise(col("Preis (netto)"))
+    let sr_df = as_money(
+        &CsvReadOptions::default()
+            .with_has_header(true)
+            .with_infer_schema_length(Some(1500))
+            .with_parse_options(parse_options.clone())
+            .try_into_reader_with_file_path(Some(sales_report.into()))?
+            .finish()?,
+    )?
+    .lazy()
+    .with_column(
+        col("Preis (netto)")
+            .fill_null(col("Preis (brutto)"))
             .alias("Preis (netto)"),
+    )
+    .with_column(
+        when(
+            col("Beschreibung")
+                .eq(lit("SCHICHTWECHSEL"))
+                .or(col("Beschreibung").eq(lit("Mittagstisch-Nachmittag")))
+                .or(col("Beschreibung").eq(lit("Nachmittag-Abend"))),
         )
-        .collect()?;
-    let txr_df = CsvReadOptions::default()
-        .with_has_header(true)
-        .with_infer_schema_length(Some(1500))
-        .with_parse_options(parse_options)
-        .try_into_reader_with_file_path(Some(transaction_report.into()))?
-        .finish()?
-        .lazy()
-        .with_column(
-            col("Netto")
-                .fill_null(col("Betrag inkl. MwSt."))
-                .alias("Netto"),
+        .then(zero())
+        .otherwise(col("Preis (brutto)"))
+        .alias("Preis (brutto)"),
+    )
+    .with_column(
+        when(
+            col("Beschreibung")
+                .eq(lit("SCHICHTWECHSEL"))
+                .or(col("Beschreibung").eq(lit("Mittagstisch-Nachmittag")))
+                .or(col("Beschreibung").eq(lit("Nachmittag-Abend"))),
         )
-        .collect()?;
+        .then(zero())
+        .otherwise(col("Preis (netto)"))
+        .alias("Preis (netto)"),
+    )
+    .collect()?;
+    let txr_df = as_money(
+        &CsvReadOptions::default()
+            .with_has_header(true)
+            .with_infer_schema_length(Some(1500))
+            .with_parse_options(parse_options)
+            .try_into_reader_with_file_path(Some(transaction_report.into()))?
+            .finish()?,
+    )?
+    .lazy()
+    .with_column(
+        col("Netto")
+            .fill_null(col("Betrag inkl. MwSt."))
+            .alias("Netto"),
+    )
+    .collect()?;
 
     fail_on_missing_trx(&txr_df, &sr_df)?;
     combine_input_dfs(&sr_df, &txr_df)
@@ -318,7 +326,7 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
         .select([
             col("Transaktions-ID"),
             col("Betrag inkl. MwSt.").alias("Commissioned Total"),
-            col("Gebühr").alias("Commission"),
+            col("Gebühr").alias("Fee"),
             col("TimeTrx"),
         ])
         .collect()?;
@@ -331,11 +339,11 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
             col("Transaktionsart")
                 .eq(lit("Umsatz"))
                 .and(col("Status").eq(lit("Erfolgreich")))
-                .and(col("Trinkgeldbetrag").fill_null(0.0).gt(0.0)),
+                .and(col("Trinkgeldbetrag").fill_null(zero()).gt(0.0)),
         )
         .select([
             col("Transaktions-ID"),
-            col("Trinkgeldbetrag").fill_null(0.0).alias("TG"),
+            col("Trinkgeldbetrag").fill_null(zero()).alias("TG"),
         ]);
 
     let additional_tip_df = clean_sr_df
@@ -359,10 +367,10 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
             col("Sku"),
             col("Währung"),
             col("TG").alias("Preis vor Rabatt"),
-            lit(0.0).alias("Rabatt"),
+            zero().alias("Rabatt"),
             col("TG").alias("Preis (brutto)"),
             col("TG").alias("Preis (netto)"),
-            lit(0.0).alias("Steuer"),
+            zero().alias("Steuer"),
             lit(NULL).alias("Steuersatz"),
             col("Konto"),
         ])
@@ -372,7 +380,7 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
     let mut union_df = clean_sr_df.vstack(&additional_tip_df)?;
     union_df.rechunk_mut();
 
-    let df = union_df
+    let items = union_df
         .lazy()
         .with_column(
             col("Datum")
@@ -421,13 +429,13 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
         )
         .with_column(
             when(col("Beschreibung").eq(lit("SCHICHTWECHSEL")))
-                .then(lit(0.0))
+                .then(zero())
                 .otherwise(col("Preis (brutto)"))
                 .alias("Preis (brutto)"),
         )
         .with_column(
             when(col("Beschreibung").eq(lit("SCHICHTWECHSEL")))
-                .then(lit(0.0))
+                .then(zero())
                 .otherwise(col("Preis (netto)"))
                 .alias("Preis (netto)"),
         )
@@ -443,11 +451,12 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
             [col("Date")],
             JoinType::Left.into(),
         )
-        .with_column(
-            (col("Preis (brutto)") / col("Commissioned Total") * col("Commission"))
-                .round(4, RoundMode::HalfToEven)
-                .alias("Commission"),
-        )
+        .collect()?;
+    let items = allocate_commission(&items)?;
+    warn_on_inexact_commission(&items)?;
+
+    let df = items
+        .lazy()
         .with_column(
             col("ChangeOfShift")
                 .fill_null(lit("14:15:00").str().to_time(time_format.clone()))
@@ -463,6 +472,7 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
             col("Type"),
             col("Transaktionsnummer").alias("Transaction ID"),
             col("Payment Method"),
+            col("Zahlungsmethode").alias("Payment Instrument"),
             col("Menge").alias("Quantity"),
             col("Beschreibung").alias("Description"),
             col("Währung").alias("Currency"),
@@ -479,6 +489,133 @@ fn combine_input_dfs(sr_df: &DataFrame, txr_df: &DataFrame) -> Result<DataFrame,
     Ok(df)
 }
 
+/// Allocates the fee of each transaction (`Fee`) to its items in proportion to their gross price,
+/// to the Rappen with the largest remainder method (see [`largest_remainder`]),
+/// so that the commissions of the items (`Commission`) sum up to the fee exactly.
+/// The commission of items without fee (e.g. cash payments) remains empty.
+fn allocate_commission(items: &DataFrame) -> Result<DataFrame, Box<dyn Error>> {
+    let rappen = items
+        .clone()
+        .lazy()
+        .select([
+            col("Transaktionsnummer"),
+            (col("Preis (brutto)") * lit(100))
+                .round(0, RoundMode::HalfToEven)
+                .cast(DataType::Int64)
+                .alias("Price"),
+            (col("Fee") * lit(100))
+                .round(0, RoundMode::HalfToEven)
+                .cast(DataType::Int64)
+                .alias("Fee"),
+        ])
+        .collect()?;
+    let prices = rappen.column("Price")?.i64()?;
+    let fees = rappen.column("Fee")?.i64()?;
+
+    let mut rows_by_trx: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (row, trx_id) in rappen
+        .column("Transaktionsnummer")?
+        .str()?
+        .iter()
+        .enumerate()
+    {
+        if let Some(trx_id) = trx_id {
+            rows_by_trx.entry(trx_id).or_default().push(row);
+        }
+    }
+    let mut commissions: Vec<Option<i64>> = vec![None; items.height()];
+    for rows in rows_by_trx.values() {
+        let Some(fee) = fees.get(rows[0]) else {
+            continue;
+        };
+        let weights: Vec<i64> = rows.iter().map(|r| prices.get(*r).unwrap_or(0)).collect();
+        if let Some(shares) = largest_remainder(fee, &weights) {
+            for (row, share) in rows.iter().zip(shares) {
+                commissions[*row] = Some(share);
+            }
+        }
+    }
+
+    let commissions = Series::new("Commission".into(), commissions);
+    let allocated = items
+        .clone()
+        .lazy()
+        .with_column((commissions.lit().cast(MONEY) / lit(100)).alias("Commission"))
+        .collect()?;
+    Ok(allocated)
+}
+
+/// Splits `total` in proportion to the `weights` with the largest remainder method:
+/// all parts are rounded down and the units left are added to the parts with the largest remainders,
+/// the first parts first in case of equal remainders.
+/// Negative weights (e.g. of refunds) are split as their opposite.
+/// Returns `None` if a non-zero `total` is to be split by weights summing up to zero.
+fn largest_remainder(total: i64, weights: &[i64]) -> Option<Vec<i64>> {
+    let sum: i64 = weights.iter().sum();
+    if total == 0 {
+        return Some(vec![0; weights.len()]);
+    }
+    if sum == 0 {
+        return None;
+    }
+    if sum < 0 {
+        let opposite: Vec<i64> = weights.iter().map(|weight| -weight).collect();
+        return largest_remainder(-total, &opposite)
+            .map(|parts| parts.iter().map(|part| -part).collect());
+    }
+    let (mut parts, remainders): (Vec<i64>, Vec<i64>) = weights
+        .iter()
+        .map(|weight| {
+            let share = total * weight;
+            (share.div_euclid(sum), share.rem_euclid(sum))
+        })
+        .unzip();
+    let left = usize::try_from(total - parts.iter().sum::<i64>()).ok()?;
+    let mut by_remainder: Vec<usize> = (0..parts.len()).collect();
+    by_remainder.sort_by_key(|i| Reverse(remainders[*i]));
+    for i in by_remainder.into_iter().take(left) {
+        parts[i] += 1;
+    }
+    Some(parts)
+}
+
+/// Lists the transactions whose allocated commissions do not sum up to the fee charged by sumup,
+/// or whose items (including the tip) do not sum up to the commissioned total,
+/// i.e. whose fee cannot be allocated exactly to the amounts charged.
+fn commission_allocation_issues(items: &DataFrame) -> PolarsResult<DataFrame> {
+    items
+        .clone()
+        .lazy()
+        .filter(col("Fee").is_not_null())
+        .group_by([col("Transaktionsnummer")])
+        .agg([
+            col("Commissioned Total").first(),
+            col("Preis (brutto)").sum().alias("Total Items"),
+            col("Fee").first(),
+            col("Commission").sum().alias("Allocated Commission"),
+        ])
+        .filter(
+            col("Allocated Commission")
+                .neq(col("Fee"))
+                .or(col("Total Items").neq(col("Commissioned Total"))),
+        )
+        .sort(["Transaktionsnummer"], SortMultipleOptions::default())
+        .collect()
+}
+
+// Outputs logs to console if the fee of one or more transactions cannot be allocated exactly
+fn warn_on_inexact_commission(items: &DataFrame) -> Result<(), Box<dyn Error>> {
+    let issues = commission_allocation_issues(items)?;
+    if issues.shape().0 > 0 {
+        println!(
+            "Transactions found whose fee cannot be allocated exactly to their items (e.g. due to the tip):"
+        );
+        println!("{issues:?}");
+        println!("Please verify the commission of those items in the intermediate document.");
+    }
+    Ok(())
+}
+
 /// Accepts Typ with various values, returning either `Cash` or `Card`
 fn infer_type() -> Expr {
     when(col("Typ").eq(lit("Verkauf")))
@@ -641,7 +778,8 @@ pub enum RecordType {
     Refund,
 }
 
-/// Payment method as defined in the sumup sales report
+/// Payment method as defined in the sumup sales report,
+/// the original payment instrument (e.g. `TWINT` or `Visa - Debitkarte`) is kept as `Payment Instrument`
 #[derive(Debug, Deserialize, Serialize, PartialEq, EnumString, Display, EnumIter)]
 pub enum PaymentMethod {
     /// Sales paid with cash
@@ -745,6 +883,50 @@ mod tests {
         assert_dataframe(&out, &intermediate_df_01);
     }
 
+    #[rstest]
+    #[case::proportional(24, &[1600, 100], &[23, 1])]
+    #[case::largest_remainder_first(10, &[100, 100, 100], &[4, 3, 3])]
+    #[case::refund(-10, &[-100, -100, -100], &[-4, -3, -3])]
+    #[case::no_fee(0, &[0], &[0])]
+    fn test_largest_remainder(
+        #[case] total: i64,
+        #[case] weights: &[i64],
+        #[case] expected: &[i64],
+    ) {
+        assert_eq!(largest_remainder(total, weights).as_deref(), Some(expected));
+    }
+
+    #[rstest]
+    fn given_fee_without_items_when_splitting_we_should_fail() {
+        assert_eq!(largest_remainder(10, &[0, 0]), None);
+    }
+
+    #[rstest]
+    fn test_commission_allocation_issues() {
+        let items = as_money(
+            &df!(
+                "Transaktionsnummer" => &["T1", "T1", "T2", "T3"],
+                "Preis (brutto)" => &[16.0, 1.0, 10.0, 5.0],
+                "Commissioned Total" => &[Some(17.0), Some(17.0), Some(12.0), None],
+                "Fee" => &[Some(0.24), Some(0.24), Some(0.2), None],
+                "Commission" => &[Some(0.23), Some(0.01), Some(0.2), None],
+            )
+            .expect("valid dataframe"),
+        )
+        .expect("amounts convertible to money");
+        let issues = commission_allocation_issues(&items).expect("should find issues");
+        assert_eq!(
+            issues
+                .column("Transaktionsnummer")
+                .expect("trx id")
+                .str()
+                .expect("text")
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec!["T2"]
+        );
+    }
+
     #[rstest]
     fn test_combine_input_dfs_with_multiple_changes_of_shift(
         sales_report_df_07: DataFrame,
@@ -1,9 +1,17 @@
 use chrono::{Duration, NaiveDate, NaiveTime};
 use polars::df;
 use polars::frame::DataFrame;
-use polars::prelude::AnyValue;
+use polars::prelude::{AnyValue, Column};
 use rstest::fixture;
 
+use crate::money::as_money;
+
+/// The sample dataframe with its amounts (floating point columns) as money,
+/// as read from the sumup reports or the intermediate file
+fn money(df: &DataFrame) -> DataFrame {
+    as_money(df).expect("amounts convertible to money")
+}
+
 #[fixture]
 pub fn sample_date() -> NaiveDate {
     NaiveDate::parse_from_str("24.3.23", "%d.%m.%y").expect("valid date")
@@ -44,7 +52,7 @@ fn sales_report_with_trx_id(
 ) -> DataFrame {
     let date = sample_date.format("%d.%m.%Y").to_string();
     let time = sample_time.format("%H:%M").to_string();
-    df!(
+    let df = df!(
         "Datum" => &[format!("{date}, {time}")],
         "Typ" => &["Verkauf"],
         "Transaktionsnummer" => &[trx_id],
@@ -62,7 +70,8 @@ fn sales_report_with_trx_id(
         "Steuersatz" => &[""],
         "Konto" => &["a@b.ch"],
     )
-    .expect("valid dataframe sales report data frame")
+    .expect("valid dataframe sales report data frame");
+    money(&df)
 }
 
 /// Sample record 01 matching the structure of the sumup transaction report csv file
@@ -83,7 +92,7 @@ fn transaction_report_with_trx_id(
 ) -> DataFrame {
     let date = sample_date.format("%Y-%m-%d").to_string();
     let time = sample_time.format("%H:%M:%S").to_string();
-    df!(
+    let df = df!(
         "Datum" => &[format!("{date} {time}")],
         "Transaktions-ID" => &[trx_id],
         "Transaktionsart" => &["Umsatz"],
@@ -93,32 +102,35 @@ fn transaction_report_with_trx_id(
         "Trinkgeldbetrag" => &[1.0],
         "Gebühr" => &[0.24],
     )
-    .expect("valid dataframe transaction report data frame 02")
+    .expect("valid dataframe transaction report data frame 02");
+    money(&df)
 }
 
 /// Sample record 01 matching the structure of the intermediate csv file,
 /// It is the result of processing `sales_report_df_01` and `transaction_report_df_01`
 #[fixture]
 pub fn intermediate_df_01(sample_date: NaiveDate, sample_time: NaiveTime) -> DataFrame {
-    df!(
+    let df = df!(
         "Account" => &["a@b.ch", "a@b.ch"],
         "Date" => &[sample_date, sample_date],
         "Time" => &[sample_time, sample_time],
         "Type" => &["Sales", "Sales"],
         "Transaction ID" => &["TEGUCXAGDE", "TEGUCXAGDE"],
         "Payment Method" => &["Card", "Card"],
+        "Payment Instrument" => &["MC", "MC"],
         "Quantity" => &[1_i64, 1_i64],
         "Description" => &["foo", "Trinkgeld"],
         "Currency" => &["CHF", "CHF"],
         "Price (Gross)" => &[16.0, 1.0],
         "Price (Net)" => &[16.0, 1.0],
-        "Commission" => &[0.2259, 0.0141],
+        "Commission" => &[0.23, 0.01],
         "Topic" => &["MiTi", "MiTi"],
         "Owner" => &["LoLa", "MiTi"],
         "Purpose" => &["Consumption", "Tip"],
         "Comment" => &[AnyValue::Null, AnyValue::Null],
     )
-    .expect("valid intermediate dataframe 01")
+    .expect("valid intermediate dataframe 01");
+    money(&df)
 }
 
 //endregion
@@ -128,13 +140,14 @@ pub fn intermediate_df_01(sample_date: NaiveDate, sample_time: NaiveTime) -> Dat
 /// Sample record 02 matching the structure of the intermediate csv file
 #[fixture]
 pub fn intermediate_df_02(sample_date: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Account" => &["a@b.ch", "a@b.ch", "a@b.ch", "a@b.ch", "a@b.ch", "a@B.ch", "a@B.ch", "a@B.ch", "a@B.ch", "a@B.ch", "a@B.ch", "a@B.ch"],
         "Date" => &[sample_date, sample_date, sample_date, sample_date, sample_date, sample_date, sample_date, sample_date, sample_date, sample_date, sample_date, sample_date],
         "Time" => &["12:32:00", "12:33:00", "12:34:00", "12:35:00", "12:36:00", "12:37:00", "12:40:00", "13:50:00", "13:51:00", "13:52:00", "13:53:00", "13:54:00"],
         "Type" => &["Sales", "Sales", "Sales", "Sales", "Sales", "Sales", "Sales", "Sales", "Sales", "Sales", "Sales", "Sales"],
         "Transaction ID" => &["TEGUCXAGDE", "TEGUCXAGDF", "TEGUCXAGDG", "TEGUCXAGDH", "TEGUCXAGDI", "TEGUCXAGDJ", "EGUCXAGDK", "EGUCXAGDI", "EGUCXAGDJ", "EGUCXAGDK", "EGUCXAGDL", "EGUCXAGDM"],
         "Payment Method" => &["Card", "Cash", "Card", "Card", "Card", "Card", "Card", "Cash", "Card", "Card", "Cash", "Cash"],
+        "Payment Instrument" => &["MC", "Bar", "MC", "MC", "MC", "MC", "MC", "Bar", "MC", "MC", "Bar", "Bar"],
         "Quantity" => &[1, 1, 4, 1, 1, 1, 1, 1, 1, 1, 1, 1],
         "Description" => &["Hauptgang, normal", "Kaffee", "Cappuccino", "Schlüsseldepot", "Kulturevent", "Rental fee", "Sold by renter, paid out in cash", "SoFe 1", "SoFe 2", "Recircle Tupper Depot", "Recircle Tupper Depot", "Hauptgang Fleisch  Reduziert"],
         "Currency" => &["CHF", "CHF", "CHF", "CHF", "CHF", "CHF", "CHF", "CHF", "CHF", "CHF", "CHF", "CHF"],
@@ -146,14 +159,15 @@ pub fn intermediate_df_02(sample_date: NaiveDate) -> DataFrame {
         "Purpose" => &["Consumption", "Consumption", "Consumption", "Consumption", "Consumption", "Consumption", "Consumption", "Consumption", "Consumption", "Consumption", "Consumption", "Consumption"],
         "Comment" => &[None::<String>, None::<String>, None::<String>, None::<String>, None::<String>, None::<String>, None::<String>, None::<String>, None::<String>, None::<String>, None::<String>, None::<String>],
     )
-        .expect("valid intermediate dataframe 02")
+        .expect("valid intermediate dataframe 02");
+    money(&df)
 }
 
 /// Sample record 02 matching the details df, created from `intermediate_df_02`
 /// details
 #[fixture]
 pub fn details_df_02(sample_date: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[sample_date],
         "SU Cash" => &[Some(46.5)],
         "SU Card" => &[Some(1166.0)],
@@ -231,7 +245,8 @@ pub fn details_df_02(sample_date: NaiveDate) -> DataFrame {
         "MealCount_Praktikum" => &[None::<i64>],
         "Total Praktikum" => &[None::<i64>],
     )
-    .expect("valid details dataframe 02")
+    .expect("valid details dataframe 02");
+    money(&df)
 }
 
 //endregion
@@ -243,7 +258,7 @@ pub fn details_df_02(sample_date: NaiveDate) -> DataFrame {
 /// miti
 #[fixture]
 pub fn details_df_03(sample_date: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[sample_date],
         "SU Cash" => &[289.0],
         "SU Card" => &[1354.0],
@@ -320,13 +335,14 @@ pub fn details_df_03(sample_date: NaiveDate) -> DataFrame {
         "MealCount_Praktikum" => &[1],
         "Total Praktikum" => &[11.0],
     )
-    .expect("valid details dataframe 02")
+    .expect("valid details dataframe 02");
+    money(&df)
 }
 
 /// Sample record 03 matching the accounting dataframe created from `details_df_03`
 #[fixture]
 pub fn accounting_df_03(sample_date: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[sample_date],
         "Payment SumUp" => &[1413.76],
         "Total Cash Debit" => &[462.0],
@@ -357,13 +373,14 @@ pub fn accounting_df_03(sample_date: NaiveDate) -> DataFrame {
         "20051/30500" => &[42.49],
         "10930/10100" => &[147.76],
     )
-    .expect("Valid accounting df 03")
+    .expect("Valid accounting df 03");
+    money(&df)
 }
 
 /// Sample record 03 matching the miti dataframe created from `details_df_03`
 #[fixture]
 pub fn miti_df_03(sample_date: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Datum" => &[sample_date],
         "Hauptgang" => &[14],
         "Reduziert" => &[1],
@@ -387,7 +404,8 @@ pub fn miti_df_03(sample_date: NaiveDate) -> DataFrame {
         "Gesponsort" => &[2.0],
         "Überweisung" => &[147.76],
     )
-    .expect("Valid miti df 03")
+    .expect("Valid miti df 03");
+    money(&df)
 }
 
 //endregion
@@ -415,13 +433,14 @@ pub fn intermediate_df_04(
     sample_date2: NaiveDate,
     sample_date3: NaiveDate,
 ) -> DataFrame {
-    df!(
+    let df = df!(
         "Account" => &["a@b.ch", "a@b.ch", "a@b.ch"],
         "Date" => &[sample_date, sample_date2, sample_date3],
         "Time" => &["21:00:00", "13:47:00", "12:30:00"],
         "Type" => &["Sales", "Sales", "Sales"],
         "Transaction ID" => &["TEGUCXAGDE", "TEGUCXAGDE", "TEGUCXAGDE"],
         "Payment Method" => &["Cash", "Card", "Cash"],
+        "Payment Instrument" => &["Bar", "MC", "Bar"],
         "Quantity" => &[1_i64, 3_i64, 1_i64],
         "Description" => &["foo", "Kindermenü", "Hauptgang Vegi Praktikum"],
         "Currency" => &["CHF", "CHF", "CHF"],
@@ -433,7 +452,8 @@ pub fn intermediate_df_04(
         "Purpose" => &["Consumption", "Consumption", "Consumption"],
         "Comment" => &[AnyValue::Null, AnyValue::Null, AnyValue::Null],
     )
-    .expect("valid intermediate dataframe 03")
+    .expect("valid intermediate dataframe 03");
+    money(&df)
 }
 
 #[fixture]
@@ -442,7 +462,7 @@ pub fn details_df_04(
     sample_date2: NaiveDate,
     sample_date3: NaiveDate,
 ) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[Some(sample_date), Some(sample_date2), Some(sample_date3), None],
         "SU Cash" => &[Some(489.1), Some(0.0), Some(11.0), Some(500.1)],
         "SU Card" => &[Some(0.0), Some(20.0), Some(0.0), Some(20.0)],
@@ -520,7 +540,8 @@ pub fn details_df_04(
         "MealCount_Praktikum" => &[None::<i64>, None::<i64>, Some(1), Some(1)],
         "Total Praktikum" => &[None::<f64>, None::<f64>, Some(11.0), Some(11.0)],
     )
-    .expect("valid details dataframe 04")
+    .expect("valid details dataframe 04");
+    money(&df)
 }
 
 //end region
@@ -529,36 +550,164 @@ pub fn details_df_04(
 
 #[fixture]
 pub fn journal_df_01() -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &["2025-01-01", "2025-05-16", "2025-07-20"],
         "Description" => &["Posting 1", "Posting 2", "Posting 3"],
         "Debit" => &["10100", "36000", "30100"],
         "Credit" => &["30100", "30700", "31000"],
         "Amount" => &[1000.0, 200.0, 100.0],
+        "Cc1" => &["", "SOMMERFEST", ""],
+        "Cc2" => &["", "", "KULTUR"],
+        "Cc3" => &["", "", ""],
+    )
+    .expect("valid journal dataframe 01");
+    money(&df)
+}
+
+#[fixture]
+pub fn accounts_df_01() -> DataFrame {
+    let df = df!(
+        "Account" => &["10100", "28000", "30100", "30700", "31000", "36000", "8900", ".SOMMERFEST"],
+        "Description" => &[
+            "Bank", "Eigenkapital", "Ertrag Café", "Ertrag Kultur", "Ertrag Vermietungen", "Spenden",
+            "Direkte Steuern", "Sommerfest",
+        ],
+        "BClass" => &["1", "2", "4", "4", "4", "4", "3", ""],
+        "Opening" => &[500.0, -500.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        "Balance" => &[1500.0, -500.0, -900.0, -200.0, -100.0, 200.0, 0.0, 200.0],
+    )
+    .expect("valid accounts dataframe 01");
+    money(&df)
+}
+
+#[fixture]
+pub fn totals_df_01() -> DataFrame {
+    let df = df!(
+        "Group" => &["1", "2", "01", "3", "4", "02", "00"],
+        "Description" => &[
+            "Totalsumme Aktiven", "Total Passiven und Eigenkapital", "Gewinn(+) Verlust(-) der Bilanz",
+            "Totalsumme Aufwand", "Totalsumme Ertrag", "Verlust(+) Gewinn(-) der Erfolgsrechnung",
+            "Differenz muss = 0 sein",
+        ],
+        "Balance" => &[1500.0, -500.0, 1000.0, 0.0, -1000.0, -1000.0, 0.0],
     )
-    .expect("valid journal dataframe 01")
+    .expect("valid totals dataframe 01");
+    money(&df)
 }
 
 #[fixture]
 pub fn aggregated_df_01_202505() -> DataFrame {
-    df!(
+    let df = df!(
        "Group" => ["Ertrag Restauration", "Spenden"],
        "Budget 2025" => [30.01, 360.01],
        "1.1.-31.5.25" => [1200.0, -200.0],
        "Verbleibend" => [1169.99, -560.01],
+       "Budget YTD" => [12.5, 150.0],
+       "Abweichung YTD" => [1187.5, -350.0],
+       "Prognose" => [2880.0, -480.0],
+       "Abweichung Prognose" => [2849.99, -840.01],
     )
-    .expect("valid journal dataframe 01")
+    .expect("valid journal dataframe 01");
+    money(&df)
 }
 
 #[fixture]
 pub fn aggregated_df_01_202507() -> DataFrame {
-    df!(
+    let df = df!(
        "Group" => ["Ertrag Restauration", "Ertrag Vermietungen", "Spenden"],
        "Budget 2025" => [30.01, 31.01, 360.01],
        "1.1.-31.7.25" => [1100.0, 100.0, -200.0],
        "Verbleibend" => [1069.99, 68.99, -560.01],
+       "Budget YTD" => [17.51, 18.09, 210.01],
+       "Abweichung YTD" => [1082.49, 81.91, -410.01],
+       "Prognose" => [1885.71, 171.43, -342.86],
+       "Abweichung Prognose" => [1855.7, 140.42, -702.87],
+    )
+    .expect("valid journal dataframe 01");
+    money(&df)
+}
+
+#[fixture]
+pub fn arranged_df_01_202505() -> DataFrame {
+    let df = df!(
+       "Group" => ["Ertrag", "Ertrag Restauration", "Spenden", "Total Ertrag", "Rein-Gew./-Verlust"],
+       "Budget 2025" => [None, Some(30.01), Some(360.01), Some(390.02), Some(390.02)],
+       "1.1.-31.5.25" => [None, Some(1200.0), Some(-200.0), Some(1000.0), Some(1000.0)],
+       "Verbleibend" => [None, Some(1169.99), Some(-560.01), Some(609.98), Some(609.98)],
+       "Budget YTD" => [None, Some(12.5), Some(150.0), Some(162.5), Some(162.5)],
+       "Abweichung YTD" => [None, Some(1187.5), Some(-350.0), Some(837.5), Some(837.5)],
+       "Prognose" => [None, Some(2880.0), Some(-480.0), Some(2400.0), Some(2400.0)],
+       "Abweichung Prognose" => [None, Some(2849.99), Some(-840.01), Some(2009.98), Some(2009.98)],
+       "Row Type" => ["Header", "Post", "Post", "Subtotal", "Result"],
+    )
+    .expect("valid arranged dataframe 01");
+    money(&df)
+}
+
+#[fixture]
+pub fn journal_df_01_previous() -> DataFrame {
+    let df = df!(
+        "Date" => &["2024-02-10", "2024-09-01"],
+        "Description" => &["Posting 1", "Posting 2"],
+        "Debit" => &["10100", "10100"],
+        "Credit" => &["30100", "36000"],
+        "Amount" => &[800.0, 50.0],
+        "Cc1" => &["", ""],
+        "Cc2" => &["", ""],
+        "Cc3" => &["", ""],
+    )
+    .expect("valid previous journal dataframe 01");
+    money(&df)
+}
+
+#[fixture]
+pub fn aggregated_df_01_202505_with_previous() -> DataFrame {
+    let df = df!(
+       "Group" => ["Ertrag Restauration", "Spenden"],
+       "Budget 2025" => [30.01, 360.01],
+       "1.1.-31.5.25" => [1200.0, -200.0],
+       "Verbleibend" => [1169.99, -560.01],
+       "Budget YTD" => [12.5, 150.0],
+       "Abweichung YTD" => [1187.5, -350.0],
+       "Prognose" => [1200.0, -480.0],
+       "Abweichung Prognose" => [1169.99, -840.01],
+       "1.1.-31.5.24" => [800.0, 0.0],
+       "Vorjahr total" => [800.0, 50.0],
+    )
+    .expect("valid aggregated dataframe 01 with previous year");
+    let mut df = money(&df);
+    df.with_column(Column::new(
+        "Delta Vorjahr %".into(),
+        [Some(50.0), None::<f64>],
+    ))
+    .expect("can add delta to the previous year");
+    df
+}
+
+#[fixture]
+pub fn drill_down_df_01_202505() -> DataFrame {
+    let df = df!(
+        "Date" => [None, Some("2025-01-01"), Some("2025-05-16"), None, None, Some("2025-05-16"), None],
+        "Description" => [
+            "Ertrag Restauration", "Posting 1", "Posting 2", "Total Ertrag Restauration",
+            "Spenden", "Posting 2", "Total Spenden",
+        ],
+        "Debit" => [None, Some("10100"), Some("36000"), None, None, Some("36000"), None],
+        "Credit" => [None, Some("30100"), Some("30700"), None, None, Some("30700"), None],
+        "Amount" => [None, Some(1000.0), Some(200.0), Some(1200.0), None, Some(-200.0), Some(-200.0)],
+        "Account" => [None, Some("30100"), Some("30700"), None, None, Some("36000"), None],
+        "Account Description" => [
+            None, Some("Ertrag Café"), Some("Ertrag Kultur"), None, None, Some("Spenden"), None,
+        ],
+        "Post" => [
+            "Ertrag Restauration", "Ertrag Restauration", "Ertrag Restauration", "Ertrag Restauration",
+            "Spenden", "Spenden", "Spenden",
+        ],
+        "Post Group" => ["Ertrag", "Ertrag", "Ertrag", "Ertrag", "Ertrag", "Ertrag", "Ertrag"],
+        "Row Type" => ["Header", "Post", "Post", "Subtotal", "Header", "Post", "Subtotal"],
     )
-    .expect("valid journal dataframe 01")
+    .expect("valid drill down dataframe 01");
+    money(&df)
 }
 
 //end region
@@ -571,13 +720,14 @@ pub fn intermediate_df_06(
     sample_date2: NaiveDate,
     sample_time: NaiveTime,
 ) -> DataFrame {
-    df!(
+    let df = df!(
         "Account" => &["a@b.ch", "a@b.ch", "a@b.ch", "a@b.ch", "a@b.ch"],
         "Date" => &[sample_date, sample_date, sample_date, sample_date2, sample_date2],
         "Time" => &[sample_time, sample_time, sample_time, sample_time, sample_time],
         "Type" => &["Sales", "Sales", "Sales", "Sales", "Sales"],
         "Transaction ID" => &["T1", "T2", "T3", "T4", "T5"],
         "Payment Method" => &["Cash", "Card", "Cash", "Cash", "Card"],
+        "Payment Instrument" => &["Bar", "TWINT", "Bar", "Bar", "MC"],
         "Quantity" => &[1_i64, 1_i64, 1_i64, 1_i64, 1_i64],
         "Description" => &["Hauptgang Vegi Standard", "Rental A", "Rental B", "Rental C", "Rental D"],
         "Currency" => &["CHF", "CHF", "CHF", "CHF", "CHF"],
@@ -589,12 +739,13 @@ pub fn intermediate_df_06(
         "Purpose" => &["Consumption", "Consumption", "Consumption", "Consumption", "Consumption"],
         "Comment" => &[AnyValue::Null, AnyValue::Null, AnyValue::Null, AnyValue::Null, AnyValue::Null],
     )
-    .expect("valid intermediate dataframe 06")
+    .expect("valid intermediate dataframe 06");
+    money(&df)
 }
 
 #[fixture]
 pub fn details_df_06(sample_date: NaiveDate, sample_date2: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[Some(sample_date), Some(sample_date2)],
         "SU Cash" => &[Some(613.0), Some(100.0)],
         "SU Card" => &[Some(400.0), Some(200.0)],
@@ -672,12 +823,13 @@ pub fn details_df_06(sample_date: NaiveDate, sample_date2: NaiveDate) -> DataFra
         "MealCount_Praktikum" => &[None::<i64>, None::<i64>],
         "Total Praktikum" => &[None::<i64>, None::<i64>],
     )
-    .expect("valid details dataframe 06")
+    .expect("valid details dataframe 06");
+    money(&df)
 }
 
 #[fixture]
 pub fn accounting_df_06(sample_date: NaiveDate, sample_date2: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[sample_date, sample_date2],
         "Payment SumUp" => &[394.0, 197.0],
         "Total Cash Debit" => &[600.0, 100.0],
@@ -708,12 +860,13 @@ pub fn accounting_df_06(sample_date: NaiveDate, sample_date2: NaiveDate) -> Data
         "20051/30500" => &[0.0, 0.0],
         "10930/10100" => &[0.0, 0.0],
     )
-    .expect("Valid accounting df 06")
+    .expect("Valid accounting df 06");
+    money(&df)
 }
 
 #[fixture]
 pub fn banana_df_06(sample_date_lom: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
     "Datum" => &[sample_date_lom, sample_date_lom, sample_date_lom],
     "Beleg" => &["", "", ""],
     "Rechnung" => &["", "", ""],
@@ -725,7 +878,8 @@ pub fn banana_df_06(sample_date_lom: NaiveDate) -> DataFrame {
     "Preis/Einheit" => &["", "", ""],
     "Betrag CHF" => &[700.0, 600.0, 9.0],
     )
-    .expect("Valid banana df 06")
+    .expect("Valid banana df 06");
+    money(&df)
 }
 
 #[fixture]
@@ -734,7 +888,7 @@ pub fn banana_df_ext_06(
     sample_date2: NaiveDate,
     sample_date_lom: NaiveDate,
 ) -> DataFrame {
-    df!(
+    let df = df!(
         "Datum" => &[sample_date, sample_date, sample_date2, sample_date2, sample_date_lom],
         "Beleg" => &["", "", "", "", ""],
         "Rechnung" => &["", "", "", "", ""],
@@ -746,7 +900,8 @@ pub fn banana_df_ext_06(
         "Preis/Einheit" => &["", "", "", "", ""],
         "Betrag CHF" => &[400.0, 600.0, 100.0, 200.0, 9.0],
     )
-    .expect("Valid banana df ext 06")
+    .expect("Valid banana df ext 06");
+    money(&df)
 }
 
 //end region
@@ -764,7 +919,7 @@ pub fn sales_report_df_07(
     let time1 = sample_time_minus_5.format("%H:%M").to_string();
     let time2 = sample_time.format("%H:%M").to_string();
     let time3 = sample_time_plus_5.format("%H:%M").to_string();
-    df!(
+    let df = df!(
     	"Datum" => &[format!("{date}, {time1}"), format!("{date}, {time2}"), format!("{date}, {time3}")],
         "Typ" => &["Verkauf", "Verkauf", "Verkauf"],
         "Transaktionsnummer" => &["T1", "T2", "T3"],
@@ -782,7 +937,8 @@ pub fn sales_report_df_07(
         "Steuersatz" => &["", "", ""],
         "Konto" => &["a@b.ch", "a@b.ch", "a@b.ch"],
     )
-    .expect("valid dataframe sales report data frame 01")
+    .expect("valid dataframe sales report data frame 01");
+    money(&df)
 }
 
 #[fixture]
@@ -796,7 +952,7 @@ pub fn transaction_report_df_07(
     let time1 = sample_time_minus_5.format("%H:%M:%S").to_string();
     let time2 = sample_time.format("%H:%M:%S").to_string();
     let time3 = sample_time_plus_5.format("%H:%M:%S").to_string();
-    df!(
+    let df = df!(
     	"Datum" => &[format!("{date} {time1}"), format!("{date} {time2}"), format!("{date} {time3}")],
         "Transaktions-ID" => &["T1", "T2", "T3"],
         "Transaktionsart" => &["Umsatz", "Umsatz", "Umsatz"],
@@ -806,7 +962,8 @@ pub fn transaction_report_df_07(
         "Trinkgeldbetrag" => &[0.0, 0.0, 0.0],
         "Gebühr" => &[0.0, 0.05, 0.0],
     )
-    .expect("valid dataframe transaction report data frame 07")
+    .expect("valid dataframe transaction report data frame 07");
+    money(&df)
 }
 
 #[fixture]
@@ -816,13 +973,14 @@ pub fn intermediate_df_07(
     sample_time_minus_5: NaiveTime,
     sample_time_plus_5: NaiveTime,
 ) -> DataFrame {
-    df!(
+    let df = df!(
         "Account" => &["a@b.ch", "a@b.ch", "a@b.ch"],
         "Date" => &[sample_date, sample_date, sample_date],
         "Time" => &[sample_time_minus_5, sample_time, sample_time_plus_5],
         "Type" => &["Sales", "Sales", "Sales"],
         "Transaction ID" => &["T1", "T2", "T3"],
         "Payment Method" => &["Cash", "Card", "Cash"],
+        "Payment Instrument" => &["Bar", "MC", "Bar"],
         "Quantity" => &[1_i64, 1_i64, 1_i64 ],
         "Description" => &["SCHICHTWECHSEL", "Kaffee", "SCHICHTWECHSEL"],
         "Currency" => &["CHF", "CHF", "CHF"],
@@ -834,7 +992,8 @@ pub fn intermediate_df_07(
         "Purpose" => &["Consumption", "Consumption", "Consumption"],
         "Comment" => &[AnyValue::Null, AnyValue::Null, AnyValue::Null],
     )
-    .expect("valid intermediate dataframe 07")
+    .expect("valid intermediate dataframe 07");
+    money(&df)
 }
 
 //end region
@@ -847,13 +1006,14 @@ pub fn intermediate_df_08(
     sample_date2: NaiveDate,
     sample_time: NaiveTime,
 ) -> DataFrame {
-    df!(
+    let df = df!(
         "Account" => &["a@b.ch", "a@b.ch", "a@b.ch", "a@b.ch", "a@b.ch"],
         "Date" => &[sample_date, sample_date, sample_date, sample_date2, sample_date2],
         "Time" => &[sample_time, sample_time, sample_time, sample_time, sample_time],
         "Type" => &["Sales", "Sales", "Sales", "Sales", "Sales"],
         "Transaction ID" => &["T1", "T2", "T3", "T4", "T5"],
         "Payment Method" => &["Cash", "Card", "Cash", "Cash", "Card"],
+        "Payment Instrument" => &["Bar", "TWINT", "Bar", "Bar", "MC"],
         "Quantity" => &[1_i64, 1_i64, 1_i64, 1_i64, 1_i64],
         "Description" => &["Hauptgang Vegi Standard", "Buffet 1 (PO)", "Buffet 1 (PO)", "Buffet 2 (PO)", "Buffet 2 (PO)"],
         "Currency" => &["CHF", "CHF", "CHF", "CHF", "CHF"],
@@ -865,12 +1025,13 @@ pub fn intermediate_df_08(
         "Purpose" => &["Consumption", "Consumption", "Consumption", "Consumption", "Consumption"],
         "Comment" => &[AnyValue::Null, AnyValue::Null, AnyValue::Null, AnyValue::Null, AnyValue::Null],
     )
-    .expect("valid intermediate dataframe 08")
+    .expect("valid intermediate dataframe 08");
+    money(&df)
 }
 
 #[fixture]
 pub fn details_df_08(sample_date: NaiveDate, sample_date2: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[Some(sample_date), Some(sample_date2)],
         "SU Cash" => &[Some(613.0), Some(100.0)],
         "SU Card" => &[Some(400.0), Some(200.0)],
@@ -948,12 +1109,13 @@ pub fn details_df_08(sample_date: NaiveDate, sample_date2: NaiveDate) -> DataFra
         "MealCount_Praktikum" => &[None::<i64>, None::<i64>],
         "Total Praktikum" => &[None::<i64>, None::<i64>],
     )
-    .expect("valid details dataframe 08")
+    .expect("valid details dataframe 08");
+    money(&df)
 }
 
 #[fixture]
 pub fn accounting_df_08(sample_date: NaiveDate, sample_date2: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
         "Date" => &[sample_date, sample_date2],
         "Payment SumUp" => &[394.0, 197.0],
         "Total Cash Debit" => &[-400.0, -200.0],
@@ -984,12 +1146,13 @@ pub fn accounting_df_08(sample_date: NaiveDate, sample_date2: NaiveDate) -> Data
         "20051/30500" => &[0.0, 0.0],
         "10930/10100" => &[0.0, 0.0],
     )
-    .expect("Valid accounting df 08")
+    .expect("Valid accounting df 08");
+    money(&df)
 }
 
 #[fixture]
 pub fn banana_df_08(sample_date_lom: NaiveDate) -> DataFrame {
-    df!(
+    let df = df!(
     "Datum" => &[sample_date_lom, sample_date_lom, sample_date_lom, sample_date_lom],
     "Beleg" => &["", "", "", ""],
     "Rechnung" => &["", "", "", ""],
@@ -1001,7 +1164,8 @@ pub fn banana_df_08(sample_date_lom: NaiveDate) -> DataFrame {
     "Preis/Einheit" => &["", "", "", ""],
     "Betrag CHF" => &[700.0, 600.0, 1300.0, 9.0],
     )
-    .expect("Valid banana df 08")
+    .expect("Valid banana df 08");
+    money(&df)
 }
 
 #[fixture]
@@ -1010,7 +1174,7 @@ pub fn banana_df_ext_08(
     sample_date2: NaiveDate,
     sample_date_lom: NaiveDate,
 ) -> DataFrame {
-    df!(
+    let df = df!(
         "Datum" => &[sample_date, sample_date, sample_date2, sample_date2, sample_date_lom, sample_date_lom],
         "Beleg" => &["", "", "", "", "", ""],
         "Rechnung" => &["", "", "", "", "", ""],
@@ -1022,7 +1186,8 @@ pub fn banana_df_ext_08(
         "Preis/Einheit" => &["", "", "", "", "", ""],
         "Betrag CHF" => &[400.0, 600.0, 100.0, 200.0, 1300.0, 9.0],
     )
-    .expect("Valid banana df ext 08")
+    .expect("Valid banana df ext 08");
+    money(&df)
 }
 
 //end region
@@ -1079,7 +1244,7 @@ fn sales_report_df_09_10(
     let d3 = format!("{date}, {time3}");
     let trx_id = "TAAAZFC7HSH";
     let refund = -19.0 - delta_refund;
-    df!(
+    let df = df!(
         "Datum" => &[d1.clone(), d1, d2, d3],
         "Typ" => &["Verkauf", "Verkauf", refund_text, "Verkauf"],
         "Transaktionsnummer" => &[trx_id, trx_id, trx_id, "TAAAZFCAHD7"],
@@ -1097,7 +1262,8 @@ fn sales_report_df_09_10(
         "Steuersatz" => &["", "", "", ""],
         "Konto" => &[Some("a@b.ch"), Some("a@b.ch"), None, Some("a@b.ch")],
     )
-    .expect("valid dataframe sales report data frame 09")
+    .expect("valid dataframe sales report data frame 09");
+    money(&df)
 }
 
 #[fixture]
@@ -1115,7 +1281,7 @@ pub fn transaction_report_df_09(
     let d2 = format!("{date} {time2}");
     let d3 = format!("{date} {time3}");
     let trx_id = "TAAAZFC7HSH";
-    df!(
+    let df = df!(
         "Datum" => &[d2, d1, d3],
         "Transaktions-ID" => &[trx_id, trx_id, "TAAAZFCAHD7"],
         "Transaktionsart" => &["Rückerstattung", "Umsatz", "Umsatz"],
@@ -1125,18 +1291,20 @@ pub fn transaction_report_df_09(
         "Trinkgeldbetrag" => &[None, Some(0.0), Some(0.0)],
         "Gebühr" => &[None, Some(0.0), Some(0.17)],
     )
-    .expect("valid dataframe transaction report data frame 09")
+    .expect("valid dataframe transaction report data frame 09");
+    money(&df)
 }
 
 #[fixture]
 pub fn intermediate_df_09(sample_date: NaiveDate, sample_time_minus_5: NaiveTime) -> DataFrame {
-    df!(
+    let df = df!(
         "Account" => &["a@b.ch"],
         "Date" => &[sample_date],
         "Time" => &[sample_time_minus_5],
         "Type" => &["Sales"],
         "Transaction ID" => &["TAAAZFCAHD7"],
         "Payment Method" => &["Card"],
+        "Payment Instrument" => &["Visa - Debitkarte"],
         "Quantity" => &[1_i64],
         "Description" => &["Hauptgang Vegi Reduziert"],
         "Currency" => &["CHF"],
@@ -1148,7 +1316,8 @@ pub fn intermediate_df_09(sample_date: NaiveDate, sample_time_minus_5: NaiveTime
         "Purpose" => &["Consumption"],
         "Comment" => &[AnyValue::Null],
     )
-    .expect("valid intermediate dataframe 09")
+    .expect("valid intermediate dataframe 09");
+    money(&df)
 }
 
 //end region